@@ -1,5 +1,7 @@
-use rand::Rng;
-use std::sync::Mutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
 use std::time::Instant;
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::TrayIconBuilder;
@@ -7,7 +9,9 @@ use tauri::Manager;
 use tauri::PhysicalSize;
 use tauri::State;
 use tauri::Emitter;
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::PathBuf;
 
@@ -22,53 +26,572 @@ use windows::Win32::UI::WindowsAndMessaging::{SystemParametersInfoW, SPI_GETWORK
 use windows::Win32::Foundation::POINT;
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
-// Default window dimensions to ensure consistency
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::SystemInformation::GetTickCount;
+// Fallback window dimensions for when the real size isn't known (yet)
 const DEFAULT_WINDOW_WIDTH: f32 = 400.0;
 const DEFAULT_WINDOW_HEIGHT: f32 = 300.0;
-const PET_WIDTH: f32 = 64.0; // Defined as constants to ensure consistency
+// Window size the pets are created for at startup, the size in tauri.conf.json.
+// window_width / window_height in pet_config.toml override it.
+const INITIAL_WINDOW_WIDTH: f32 = 1920.0;
+const INITIAL_WINDOW_HEIGHT: f32 = 1032.0;
+const MAX_WINDOW_SIZE: f32 = 16384.0;
+const PET_WIDTH: f32 = 64.0; // Default pet size, can be changed with set_pet_size
 const PET_HEIGHT: f32 = 64.0;
+const MIN_PET_SIZE: f32 = 10.0;
+// pet_count in pet_config.toml is capped here, every pet costs physics time each tick
+const MAX_STARTUP_PETS: usize = 16;
+// Label of the pet window in tauri.conf.json
+const MAIN_WINDOW_LABEL: &str = "main";
+const DEFAULT_TICK_HZ: u32 = 60;
+const MIN_TICK_HZ: u32 = 10;
+const MAX_TICK_HZ: u32 = 240;
+// The tick drops to this rate while every pet is sitting still, to save battery
+const IDLE_TICK_HZ: u32 = 10;
+// get_perf_stats averages over this many of the latest ticks
+const PERF_WINDOW: usize = 120;
+// Some window managers quietly drop always-on-top (e.g. when another topmost
+// window comes up), so the tick sets it again this often
+const ALWAYS_ON_TOP_REASSERT: std::time::Duration = std::time::Duration::from_secs(5);
+// How often the tick asks whether the window is minimized or hidden
+const VISIBILITY_CHECK: std::time::Duration = std::time::Duration::from_secs(1);
+// The pets nap once nobody has touched the keyboard or mouse for this long.
+// The tick checks every AFK_CHECK, and keeps napping pets asleep at least
+// AFK_NAP_HOLD seconds ahead so they don't wake between checks.
+const DEFAULT_AFK_NAP_AFTER: u32 = 300;
+const MAX_AFK_NAP_AFTER: u32 = 24 * 60 * 60;
+const AFK_CHECK: std::time::Duration = std::time::Duration::from_secs(2);
+const AFK_NAP_HOLD: f32 = 10.0;
+// A frame never advances more than one tick at the slowest rate
+const MAX_DELTA_TIME: f32 = 1.0 / MIN_TICK_HZ as f32;
+// Largest step the integration takes at once, so a long frame is split into
+// several small ones instead of one big jump
+const PHYSICS_SUBSTEP: f32 = 0.016;
 
 
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+// The serde names are the animation keys the frontend looks up, so they are
+// spelled out where the kebab-case conversion would get them wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 enum AnimationState {
     IdleRight,
     IdleLeft,
+    #[serde(rename = "sleep-right")]
     SleepingRight,
+    #[serde(rename = "sleep-left")]
     SleepingLeft,
+    #[serde(rename = "idle-alt-1-right")]
     IdleAlt1Right,
+    #[serde(rename = "idle-alt-1-left")]
     IdleAlt1Left,
+    #[serde(rename = "idle-alt-2-right")]
     IdleAlt2Right,
+    #[serde(rename = "idle-alt-2-left")]
     IdleAlt2Left,
+    #[serde(rename = "run-right")]
     RunningRight,
+    #[serde(rename = "run-left")]
     RunningLeft,
+    #[serde(rename = "jump-right")]
     JumpingRight,
+    #[serde(rename = "jump-left")]
     JumpingLeft,
+    #[serde(rename = "fall-right")]
     FallingRight,
+    #[serde(rename = "fall-left")]
     FallingLeft,
+    LandingRight,
+    LandingLeft,
+    CeilingRight,
+    CeilingLeft,
+    HappyRight,
+    HappyLeft,
+    // A subtle bob swapped in now and then while idling, so the pet doesn't look frozen
+    IdleBreathingRight,
+    IdleBreathingLeft,
+    // Jumping out of its skin after startle
+    StartledRight,
+    StartledLeft,
 }
 
 impl AnimationState {
-    fn to_string(&self) -> &'static str {
+    // Every variant, for list_animation_states. Checked against the enum below.
+    const ALL: [AnimationState; 24] = [
+        AnimationState::IdleRight,
+        AnimationState::IdleLeft,
+        AnimationState::SleepingRight,
+        AnimationState::SleepingLeft,
+        AnimationState::IdleAlt1Right,
+        AnimationState::IdleAlt1Left,
+        AnimationState::IdleAlt2Right,
+        AnimationState::IdleAlt2Left,
+        AnimationState::RunningRight,
+        AnimationState::RunningLeft,
+        AnimationState::JumpingRight,
+        AnimationState::JumpingLeft,
+        AnimationState::FallingRight,
+        AnimationState::FallingLeft,
+        AnimationState::LandingRight,
+        AnimationState::LandingLeft,
+        AnimationState::CeilingRight,
+        AnimationState::CeilingLeft,
+        AnimationState::HappyRight,
+        AnimationState::HappyLeft,
+        AnimationState::IdleBreathingRight,
+        AnimationState::IdleBreathingLeft,
+        AnimationState::StartledRight,
+        AnimationState::StartledLeft,
+    ];
+
+    // The same animation, looking the other way if needed
+    fn facing(self, right: bool) -> AnimationState {
+        use AnimationState::*;
+
+        let (right_state, left_state) = match self {
+            IdleRight | IdleLeft => (IdleRight, IdleLeft),
+            SleepingRight | SleepingLeft => (SleepingRight, SleepingLeft),
+            IdleAlt1Right | IdleAlt1Left => (IdleAlt1Right, IdleAlt1Left),
+            IdleAlt2Right | IdleAlt2Left => (IdleAlt2Right, IdleAlt2Left),
+            RunningRight | RunningLeft => (RunningRight, RunningLeft),
+            JumpingRight | JumpingLeft => (JumpingRight, JumpingLeft),
+            FallingRight | FallingLeft => (FallingRight, FallingLeft),
+            LandingRight | LandingLeft => (LandingRight, LandingLeft),
+            CeilingRight | CeilingLeft => (CeilingRight, CeilingLeft),
+            HappyRight | HappyLeft => (HappyRight, HappyLeft),
+            IdleBreathingRight | IdleBreathingLeft => (IdleBreathingRight, IdleBreathingLeft),
+            StartledRight | StartledLeft => (StartledRight, StartledLeft),
+        };
+
+        if right { right_state } else { left_state }
+    }
+
+    // The animation key, the same as the serde name
+    fn name(self) -> &'static str {
+        use AnimationState::*;
+
+        match self {
+            IdleRight => "idle-right",
+            IdleLeft => "idle-left",
+            SleepingRight => "sleep-right",
+            SleepingLeft => "sleep-left",
+            IdleAlt1Right => "idle-alt-1-right",
+            IdleAlt1Left => "idle-alt-1-left",
+            IdleAlt2Right => "idle-alt-2-right",
+            IdleAlt2Left => "idle-alt-2-left",
+            RunningRight => "run-right",
+            RunningLeft => "run-left",
+            JumpingRight => "jump-right",
+            JumpingLeft => "jump-left",
+            FallingRight => "fall-right",
+            FallingLeft => "fall-left",
+            LandingRight => "landing-right",
+            LandingLeft => "landing-left",
+            CeilingRight => "ceiling-right",
+            CeilingLeft => "ceiling-left",
+            HappyRight => "happy-right",
+            HappyLeft => "happy-left",
+            IdleBreathingRight => "idle-breathing-right",
+            IdleBreathingLeft => "idle-breathing-left",
+            StartledRight => "startled-right",
+            StartledLeft => "startled-left",
+        }
+    }
+}
+
+impl std::str::FromStr for AnimationState {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        AnimationState::ALL
+            .into_iter()
+            .find(|state| state.name() == name)
+            .ok_or_else(|| format!("Unknown animation state: {}", name))
+    }
+}
+
+// ALL is kept by hand, so check it when building. The match has no wildcard,
+// so a new variant doesn't compile until it gets a slot here, and the loop
+// fails the build unless ALL holds every variant in its slot.
+const _: () = {
+    const fn slot(state: AnimationState) -> usize {
+        use AnimationState::*;
+
+        match state {
+            IdleRight => 0,
+            IdleLeft => 1,
+            SleepingRight => 2,
+            SleepingLeft => 3,
+            IdleAlt1Right => 4,
+            IdleAlt1Left => 5,
+            IdleAlt2Right => 6,
+            IdleAlt2Left => 7,
+            RunningRight => 8,
+            RunningLeft => 9,
+            JumpingRight => 10,
+            JumpingLeft => 11,
+            FallingRight => 12,
+            FallingLeft => 13,
+            LandingRight => 14,
+            LandingLeft => 15,
+            CeilingRight => 16,
+            CeilingLeft => 17,
+            HappyRight => 18,
+            HappyLeft => 19,
+            IdleBreathingRight => 20,
+            IdleBreathingLeft => 21,
+            StartledRight => 22,
+            StartledLeft => 23,
+        }
+    }
+
+    let mut index = 0;
+    while index < AnimationState::ALL.len() {
+        assert!(slot(AnimationState::ALL[index]) == index, "AnimationState::ALL is missing or repeats a variant");
+        index += 1;
+    }
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum BehaviorMode {
+    Wander,
+    FollowCursor,
+    FleeCursor,
+    ManualControl, // only moves when told to by move_left / move_right / jump
+    WanderTarget,  // eases towards random spots on the floor, pausing at each one
+    PerimeterWalk, // walks all the way round the window, turning the corner onto each edge
+}
+
+// Which edge of the window the pet treats as the floor
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum GravityDirection {
+    Down,
+    Up,
+    Left,
+    Right,
+}
+
+// The physics is written for gravity pointing down. For the other directions
+// the pet is moved into a rotated "local" frame where it does, stepped there,
+// and moved back. In the local frame x runs along the floor and y towards it;
+// the pet's width lies along the floor and its height points away from it.
+impl GravityDirection {
+    fn is_sideways(self) -> bool {
+        matches!(self, GravityDirection::Left | GravityDirection::Right)
+    }
+
+    // The edge a pet walking off the end of this one climbs onto. Walking
+    // local right goes floor, right wall, ceiling, left wall and back round.
+    fn next_edge(self, walking_right: bool) -> GravityDirection {
+        match (self, walking_right) {
+            (GravityDirection::Down, true) | (GravityDirection::Up, false) => GravityDirection::Right,
+            (GravityDirection::Right, true) | (GravityDirection::Left, false) => GravityDirection::Up,
+            (GravityDirection::Up, true) | (GravityDirection::Down, false) => GravityDirection::Left,
+            (GravityDirection::Left, true) | (GravityDirection::Right, false) => GravityDirection::Down,
+        }
+    }
+
+    // Window size as seen from the local frame
+    fn local_window(self, window_width: f32, window_height: f32) -> (f32, f32) {
+        if self.is_sideways() {
+            (window_height, window_width)
+        } else {
+            (window_width, window_height)
+        }
+    }
+
+    // Top-left corner of a pet-sized box, screen to local
+    fn box_to_local(self, x: f32, y: f32, window: (f32, f32), size: (f32, f32)) -> (f32, f32) {
+        let (window_width, window_height) = window;
+        let (pet_width, pet_height) = size;
+
+        match self {
+            GravityDirection::Down => (x, y),
+            GravityDirection::Up => (window_width - pet_width - x, window_height - pet_height - y),
+            GravityDirection::Left => (y, window_width - pet_height - x),
+            GravityDirection::Right => (window_height - pet_width - y, x),
+        }
+    }
+
+    // Top-left corner of a pet-sized box, local to screen
+    fn box_to_screen(self, x: f32, y: f32, window: (f32, f32), size: (f32, f32)) -> (f32, f32) {
+        let (window_width, window_height) = window;
+        let (pet_width, pet_height) = size;
+
+        match self {
+            GravityDirection::Down => (x, y),
+            GravityDirection::Up => (window_width - pet_width - x, window_height - pet_height - y),
+            GravityDirection::Left => (window_width - pet_height - y, x),
+            GravityDirection::Right => (y, window_height - pet_width - x),
+        }
+    }
+
+    fn point_to_local(self, x: f32, y: f32, window: (f32, f32)) -> (f32, f32) {
+        self.box_to_local(x, y, window, (0.0, 0.0))
+    }
+
+    fn point_to_screen(self, x: f32, y: f32, window: (f32, f32)) -> (f32, f32) {
+        self.box_to_screen(x, y, window, (0.0, 0.0))
+    }
+
+    fn vector_to_local(self, x: f32, y: f32) -> (f32, f32) {
+        match self {
+            GravityDirection::Down => (x, y),
+            GravityDirection::Up => (-x, -y),
+            GravityDirection::Left => (y, -x),
+            GravityDirection::Right => (-y, x),
+        }
+    }
+
+    fn vector_to_screen(self, x: f32, y: f32) -> (f32, f32) {
         match self {
-            AnimationState::IdleRight => "idle-right",
-            AnimationState::IdleLeft => "idle-left",
-            AnimationState::SleepingRight => "sleep-right",
-            AnimationState::SleepingLeft => "sleep-left",
-            AnimationState::IdleAlt1Right => "idle-alt-1-right",
-            AnimationState::IdleAlt1Left => "idle-alt-1-left",
-            AnimationState::IdleAlt2Right => "idle-alt-2-right",
-            AnimationState::IdleAlt2Left => "idle-alt-2-left",
-            AnimationState::RunningRight => "run-right",
-            AnimationState::RunningLeft => "run-left",
-            AnimationState::JumpingRight => "jump-right",
-            AnimationState::JumpingLeft => "jump-left",
-            AnimationState::FallingRight => "fall-right",
-            AnimationState::FallingLeft => "fall-left",
+            GravityDirection::Down => (x, y),
+            GravityDirection::Up => (-x, -y),
+            GravityDirection::Left => (-y, x),
+            GravityDirection::Right => (y, -x),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+struct BehaviorConfig {
+    sleep_after_idle: f32, // seconds of sitting still before dozing off
+    // Relative weights for what the pet does when it gets bored of idling
+    walk_weight: f32,
+    run_weight: f32,
+    jump_weight: f32,
+    flee_radius: f32, // in flee mode, the pet only runs once the cursor is this close
+    idle_breathing: bool, // alternate between idle and the breathing bob while still
+    face_away_from_walls: bool, // settling next to a wall, turn towards the middle of the screen
+    // Accessibility: no jumping, hopping or running, just gentle walking with
+    // slower animations and no squash-and-stretch
+    reduced_motion: bool,
+    // Fading out while nothing happens: after how many idle seconds, how much
+    // opacity goes per second (0 never fades) and how faint the pet can get
+    fade_after_idle: f32,
+    fade_rate: f32,
+    min_opacity: f32,
+    // Wander-target mode: how hard the pet speeds up and slows down (px/s²),
+    // how far out it starts easing off, and how long it waits at each spot
+    target_acceleration: f32,
+    target_arrive_radius: f32,
+    target_pause: f32,
+    min_ground_dwell: f32, // seconds on the ground after landing before it wanders off again
+    // Bursts of energy while wandering: how many a minute on average (0 turns
+    // them off), how long each lasts and how much likelier jumping gets
+    bursts_per_minute: f32,
+    burst_duration: f32,
+    burst_intensity: f32,
+}
+
+impl BehaviorConfig {
+    fn new() -> Self {
+        Self {
+            sleep_after_idle: 15.0,
+            walk_weight: 0.35,
+            run_weight: 0.45,
+            jump_weight: 0.15,
+            flee_radius: DEFAULT_FLEE_RADIUS,
+            idle_breathing: true,
+            face_away_from_walls: true,
+            reduced_motion: false,
+            fade_after_idle: 30.0,
+            fade_rate: 0.0,
+            min_opacity: 0.3,
+            target_acceleration: 400.0,
+            target_arrive_radius: 120.0,
+            target_pause: 2.0,
+            min_ground_dwell: 0.5,
+            bursts_per_minute: 0.0,
+            burst_duration: 5.0,
+            burst_intensity: 4.0,
+        }
+    }
+
+    // Share of the pet's idle decisions that are jumps. Jumps are rolled every
+    // frame, at this share of a decision per average idle period.
+    fn jump_probability(&self) -> f32 {
+        let total_weight = self.walk_weight + self.run_weight + self.jump_weight;
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+
+        self.jump_weight / total_weight
+    }
+
+    // Rescale the jump weight so it makes up `probability` of the total,
+    // keeping the walk/run balance as it was
+    fn set_jump_probability(&mut self, probability: f32) {
+        if probability >= 1.0 {
+            self.walk_weight = 0.0;
+            self.run_weight = 0.0;
+            self.jump_weight = 1.0;
+            return;
+        }
+
+        if self.walk_weight + self.run_weight <= 0.0 {
+            // Only jumping was enabled, bring back the default walk/run mix
+            let defaults = Self::new();
+            self.walk_weight = defaults.walk_weight;
+            self.run_weight = defaults.run_weight;
+        }
+
+        let other_weight = self.walk_weight + self.run_weight;
+        self.jump_weight = other_weight * probability / (1.0 - probability);
+    }
+
+    // The checks behind each behavior command, also run on imported pets
+    fn validate(&self) -> Result<(), String> {
+        Self::check_idle_fade(self.fade_after_idle, self.fade_rate, self.min_opacity)?;
+        Self::check_ground_dwell(self.min_ground_dwell)?;
+        Self::check_energy_bursts(self.bursts_per_minute, self.burst_duration, self.burst_intensity)?;
+        Self::check_sleep_threshold(self.sleep_after_idle)?;
+        Self::check_flee_radius(self.flee_radius)?;
+        Self::check_wander_weights(self.walk_weight, self.run_weight, self.jump_weight)?;
+        Self::check_wander_target(self.target_acceleration, self.target_arrive_radius, self.target_pause)
+    }
+
+    fn check_idle_fade(after_seconds: f32, rate: f32, min_opacity: f32) -> Result<(), String> {
+        if [after_seconds, rate].iter().any(|value| !value.is_finite() || *value < 0.0) {
+            return Err("Fade delay and rate must be non-negative numbers".to_string());
+        }
+        if !(0.0..=1.0).contains(&min_opacity) {
+            return Err(format!("Minimum opacity must be between 0 and 1, got {}", min_opacity));
+        }
+        Ok(())
+    }
+
+    fn check_ground_dwell(seconds: f32) -> Result<(), String> {
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(format!("Ground dwell must be a non-negative number of seconds, got {}", seconds));
+        }
+        Ok(())
+    }
+
+    fn check_energy_bursts(per_minute: f32, duration: f32, intensity: f32) -> Result<(), String> {
+        if [per_minute, duration].iter().any(|value| !value.is_finite() || *value < 0.0) {
+            return Err("Burst frequency and duration must be non-negative numbers".to_string());
+        }
+        if !intensity.is_finite() || intensity < 1.0 {
+            return Err(format!("Burst intensity must be at least 1, got {}", intensity));
+        }
+        Ok(())
+    }
+
+    fn check_sleep_threshold(seconds: f32) -> Result<(), String> {
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(format!("Sleep threshold must be a non-negative number of seconds, got {}", seconds));
+        }
+        Ok(())
+    }
+
+    fn check_flee_radius(radius: f32) -> Result<(), String> {
+        if !radius.is_finite() || radius < 0.0 {
+            return Err(format!("Flee radius must be a non-negative number, got {}", radius));
+        }
+        Ok(())
+    }
+
+    fn check_wander_weights(walk: f32, run: f32, jump: f32) -> Result<(), String> {
+        if [walk, run, jump].iter().any(|weight| !weight.is_finite() || *weight < 0.0) {
+            return Err("Wander weights must be non-negative numbers".to_string());
+        }
+        Ok(())
+    }
+
+    fn check_wander_target(acceleration: f32, arrive_radius: f32, pause: f32) -> Result<(), String> {
+        if !acceleration.is_finite() || acceleration <= 0.0 {
+            return Err(format!("Acceleration must be a positive number, got {}", acceleration));
+        }
+        if [arrive_radius, pause].iter().any(|value| !value.is_finite() || *value < 0.0) {
+            return Err("Arrive radius and pause must be non-negative numbers".to_string());
         }
+        Ok(())
+    }
+}
+
+impl Default for BehaviorConfig {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+// Jumps available between landings, the first one is used leaving the ground
+const MAX_JUMPS: u8 = 2;
+// Chance per second that a falling pet spends its remaining jump mid-air
+const DOUBLE_JUMP_CHANCE_PER_SECOND: f32 = 0.8;
+// Average idle period, normally and during a burst of energy (the middle of
+// the ranges idle_duration is picked from). An idle pet jumps at its jump
+// share per period, so jump_probability keeps its meaning as a rate.
+const MEAN_IDLE_PERIOD: f32 = 2.5;
+const BURST_MEAN_IDLE_PERIOD: f32 = 0.5;
+
+// A walking pet counts as having reached its target within this distance
+const WALK_ARRIVE_DISTANCE: f32 = 4.0;
+// An idle pet within this distance of its home is already there
+const HOME_RADIUS: f32 = 48.0;
+
+// Stamina runs from 0 to MAX_STAMINA. Each jump costs some and it comes back
+// while the pet rests on the ground; a tired pet jumps less and naps more.
+const MAX_STAMINA: f32 = 100.0;
+const STAMINA_PER_JUMP: f32 = 15.0;
+const STAMINA_REGEN_PER_SECOND: f32 = 5.0;
+const LOW_STAMINA: f32 = 25.0;
+
+// Chance that a bored pet climbs up and walks along the top of the window
+const CEILING_CLIMB_CHANCE: f32 = 0.05;
+
+// How long the happy reaction to a click plays
+const REACTION_DURATION: f32 = 0.6;
+// startle: how long the startled pose lasts, how soon the pet can be startled
+// again, and how much of a full jump it leaps
+const STARTLE_DURATION: f32 = 0.5;
+const STARTLE_COOLDOWN: f32 = 1.5;
+const STARTLE_JUMP: f32 = 0.7;
+// Longest a scripted animation can hold, ten minutes
+const MAX_FORCED_ANIMATION_MS: u64 = 10 * 60 * 1000;
+
+// How long the landing squash plays after touching down from a fall
+const LANDING_DURATION: f32 = 0.15;
+// The floor holds the pet back, so only this much of the wind gets through
+const GROUND_WIND_FACTOR: f32 = 0.3;
+// Gusts swing the wind between calm and this many times its set strength
+const MAX_GUST_FACTOR: f32 = 2.0;
+const GUST_CHANGE_CHANCE_PER_SECOND: f32 = 0.5;
+// Ground speed at which the run cycle plays at its normal rate; the rate
+// follows the speed from there, within these limits
+const RUN_CYCLE_SPEED: f32 = 200.0;
+const MIN_ANIMATION_SPEED: f32 = 0.5;
+const MAX_ANIMATION_SPEED: f32 = 2.0;
+// Squash and stretch never bends the sprite by more than this either way
+const MAX_SQUASH: f32 = 0.2;
+// Rising at the jump force or faster gets the full stretch
+const STRETCH_SPEED: f32 = 480.0;
+// Downward speed a pet spawned at the top starts its fall with
+const SPAWN_DROP_SPEED: f32 = 50.0;
+// How long each half of the idle breathing cycle lasts
+const BREATHING_INTERVAL: f32 = 2.0;
+// Reduced motion: fastest the pet moves along the ground (px/s) and how much
+// slower its animations play
+const REDUCED_MOTION_SPEED: f32 = 60.0;
+const REDUCED_MOTION_ANIMATION_SPEED: f32 = 0.6;
+// Settling down this close to a wall counts as being against it
+const WALL_FACING_MARGIN: f32 = 24.0;
+
+// Below this horizontal speed the pet counts as sitting still
+const IDLE_SPEED_THRESHOLD: f32 = 5.0;
+
+// How close (horizontally) the pet gets before it stops chasing the cursor
+const FOLLOW_STOP_DISTANCE: f32 = 16.0;
+// How far above the pet the cursor must be before the pet jumps for it
+const FOLLOW_JUMP_HEIGHT: f32 = 96.0;
+const DEFAULT_FLEE_RADIUS: f32 = 150.0;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum EmotionState {
     Lonely,
@@ -94,6 +617,339 @@ impl PetNeeds {
     }
 }
 
+// Upper bounds for the tunable physics values. With the physics substep these
+// keep the pet from covering more than its own height in a single step.
+const MAX_GRAVITY: f32 = 5000.0;
+const MAX_JUMP_FORCE: f32 = 1200.0;
+const MAX_SPEED_X: f32 = 1000.0;
+const MAX_AIR_DRAG: f32 = 10.0;
+const MAX_GROUND_FRICTION: f32 = 20.0;
+const MAX_EDGE_OFFSET: f32 = 500.0;
+const MAX_TIME_SCALE: f32 = 4.0;
+// About ten seconds of flight at the physics substep
+const MAX_PREDICTION_STEPS: u32 = 600;
+const TRAIL_CAPACITY: usize = 32; // past positions kept for the motion trail
+const MAX_WIND: f32 = 2000.0;
+const MAX_BOUNCE_THRESHOLD: f32 = 1000.0;
+const MAX_LANDING_EVENT_SPEED: f32 = 5000.0;
+const MAX_LANDING_EVENT_COOLDOWN: f32 = 5.0;
+
+// Missing fields (e.g. from an older save file) fall back to the defaults
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+struct PhysicsConfig {
+    gravity: f32,
+    jump_force: f32, // negative is upwards
+    max_speed_x: f32,
+    // Fraction of speed kept when bouncing off each edge
+    restitution_left: f32,
+    restitution_right: f32,
+    restitution_floor: f32, // 0.0 sticks the landing
+    bounce_threshold: f32,  // a bounce slower than this (px/s) settles instead, so it doesn't jitter forever
+    air_drag: f32,          // fraction of horizontal speed lost per second in the air
+    ground_friction: f32,   // how quickly an idle pet slides to a stop
+    gravity_direction: GravityDirection,
+    terminal_velocity: f32, // fastest the pet can fall
+    // Space kept clear at the bottom and top of the window, e.g. for a dock
+    // or taskbar that the window overlaps
+    ground_offset: f32,
+    top_offset: f32,
+    time_scale: f32, // 1.0 is real time, 0.5 half speed, 0.0 frozen
+    spawn_from_top: bool, // new and reset pets drop in from the top instead of standing on the floor
+    // How much velocity two bumping pets trade, 0 just stops them overlapping,
+    // 1 swaps their speeds like billiard balls
+    collision_response: f32,
+    wind: f32,   // steady sideways push in px/s², positive blows right
+    gusty: bool, // let the wind rise and fall at random
+    // pet-landed only fires for landings at least this hard (px/s), and no
+    // sooner than the cooldown (seconds) after the last one, so bouncing
+    // along a wall doesn't spam landing sounds
+    landing_event_min_speed: f32,
+    landing_event_cooldown: f32,
+}
+
+impl PhysicsConfig {
+    fn new() -> Self {
+        Self {
+            gravity: 980.0,
+            jump_force: -480.0,
+            max_speed_x: 200.0,
+            restitution_left: 0.5,
+            restitution_right: 0.5,
+            restitution_floor: 0.0,
+            bounce_threshold: 40.0,
+            air_drag: 0.5,
+            ground_friction: 6.0,
+            gravity_direction: GravityDirection::Down,
+            terminal_velocity: 1200.0,
+            ground_offset: 0.0,
+            top_offset: 0.0,
+            time_scale: 1.0,
+            spawn_from_top: false,
+            collision_response: 0.8,
+            wind: 0.0,
+            gusty: false,
+            landing_event_min_speed: 80.0,
+            landing_event_cooldown: 0.2,
+        }
+    }
+
+    // The checks behind each physics command, also run on imported pets so a
+    // blob can't carry in values the commands would refuse
+    fn validate(&self) -> Result<(), String> {
+        Self::check_motion(self.gravity, self.jump_force, self.max_speed_x)?;
+        // set_physics_config clamps these, so a stored pet is always inside
+        if self.gravity > MAX_GRAVITY
+            || self.jump_force.abs() > MAX_JUMP_FORCE
+            || !(0.0..=MAX_SPEED_X).contains(&self.max_speed_x)
+        {
+            return Err("Gravity, jump force or max speed is past its limit".to_string());
+        }
+        Self::check_restitution(self.restitution_left, self.restitution_right, self.restitution_floor)?;
+        Self::check_terminal_velocity(self.terminal_velocity)?;
+        Self::check_collision_response(self.collision_response)?;
+        Self::check_landing_events(self.landing_event_min_speed, self.landing_event_cooldown)?;
+        Self::check_bounce_threshold(self.bounce_threshold)?;
+        Self::check_wind(self.wind)?;
+        Self::check_time_scale(self.time_scale)?;
+        Self::check_edge_offsets(self.ground_offset, self.top_offset)?;
+        Self::check_air_drag(self.air_drag)?;
+        Self::check_ground_friction(self.ground_friction)
+    }
+
+    // Out of range jump force and speed are clamped rather than refused
+    fn check_motion(gravity: f32, jump_force: f32, max_speed_x: f32) -> Result<(), String> {
+        if !gravity.is_finite() || gravity < 0.0 {
+            return Err(format!("Gravity must be a non-negative number, got {}", gravity));
+        }
+        if !jump_force.is_finite() || !max_speed_x.is_finite() {
+            return Err("Jump force and max speed must be finite numbers".to_string());
+        }
+        Ok(())
+    }
+
+    fn check_restitution(left: f32, right: f32, floor: f32) -> Result<(), String> {
+        if [left, right, floor].iter().any(|value| !(0.0..=1.0).contains(value)) {
+            return Err("Restitution values must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+
+    fn check_terminal_velocity(speed: f32) -> Result<(), String> {
+        if !speed.is_finite() || speed <= 0.0 {
+            return Err(format!("Terminal velocity must be a positive number, got {}", speed));
+        }
+        Ok(())
+    }
+
+    fn check_collision_response(factor: f32) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&factor) {
+            return Err(format!("Collision response must be between 0 and 1, got {}", factor));
+        }
+        Ok(())
+    }
+
+    fn check_landing_events(min_speed: f32, cooldown: f32) -> Result<(), String> {
+        if !(0.0..=MAX_LANDING_EVENT_SPEED).contains(&min_speed) {
+            return Err(format!(
+                "Minimum landing speed must be between 0 and {}, got {}",
+                MAX_LANDING_EVENT_SPEED, min_speed
+            ));
+        }
+        if !(0.0..=MAX_LANDING_EVENT_COOLDOWN).contains(&cooldown) {
+            return Err(format!(
+                "Landing cooldown must be at most {}ms, got {}",
+                MAX_LANDING_EVENT_COOLDOWN * 1000.0,
+                cooldown * 1000.0
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_bounce_threshold(speed: f32) -> Result<(), String> {
+        if !(0.0..=MAX_BOUNCE_THRESHOLD).contains(&speed) {
+            return Err(format!(
+                "Bounce threshold must be between 0 and {}, got {}",
+                MAX_BOUNCE_THRESHOLD, speed
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_wind(force: f32) -> Result<(), String> {
+        if !force.is_finite() || force.abs() > MAX_WIND {
+            return Err(format!("Wind must be between -{0} and {0}, got {1}", MAX_WIND, force));
+        }
+        Ok(())
+    }
+
+    fn check_time_scale(scale: f32) -> Result<(), String> {
+        if !(0.0..=MAX_TIME_SCALE).contains(&scale) {
+            return Err(format!("Time scale must be between 0 and {}, got {}", MAX_TIME_SCALE, scale));
+        }
+        Ok(())
+    }
+
+    // Only the range, whether the pet still fits depends on its window
+    fn check_edge_offsets(bottom: f32, top: f32) -> Result<(), String> {
+        if ![bottom, top].iter().all(|offset| (0.0..=MAX_EDGE_OFFSET).contains(offset)) {
+            return Err(format!("Edge offsets must be between 0 and {}px", MAX_EDGE_OFFSET));
+        }
+        Ok(())
+    }
+
+    fn check_air_drag(drag: f32) -> Result<(), String> {
+        if !(0.0..=MAX_AIR_DRAG).contains(&drag) {
+            return Err(format!("Air drag must be between 0.0 and {}", MAX_AIR_DRAG));
+        }
+        Ok(())
+    }
+
+    fn check_ground_friction(friction: f32) -> Result<(), String> {
+        if !(0.0..=MAX_GROUND_FRICTION).contains(&friction) {
+            return Err(format!("Ground friction must be between 0.0 and {}", MAX_GROUND_FRICTION));
+        }
+        Ok(())
+    }
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// A point the pet is pulled towards, as if it had its own gravity. The pull
+// is strength / distance in px/s², so strength is in px²/s².
+#[derive(Debug, Clone, Copy)]
+struct Attractor {
+    x: f32,
+    y: f32,
+    strength: f32,
+}
+
+// Attractor pull at most this strong, and distances below the minimum count
+// as the minimum, so the pull stays finite right on top of the point
+const MAX_ATTRACTOR_STRENGTH: f32 = 1_000_000.0;
+const MAX_ATTRACTOR_ACCEL: f32 = 3000.0;
+const ATTRACTOR_MIN_DISTANCE: f32 = 16.0;
+
+// Cap on how fast a released pet can be thrown
+const MAX_THROW_SPEED: f32 = 1200.0;
+// Thrown this hard into the floor from right next to it, the pet goes to the tray
+const DISMISS_THROW_SPEED: f32 = 900.0;
+const DISMISS_FLOOR_MARGIN: f32 = 16.0;
+// How much each new cursor sample contributes to the smoothed drag velocity
+const DRAG_SMOOTHING: f32 = 0.5;
+
+// How an animation should be played, when the config overrides the
+// frontend's built-in timing for it
+#[derive(Debug, Clone, Copy, Serialize)]
+struct AnimationMetadata {
+    frame_count: u32,
+    fps: f32,
+}
+
+const MAX_ANIMATION_FRAMES: f32 = 64.0;
+const MAX_ANIMATION_FPS: f32 = 60.0;
+
+// A named bundle of settings, so users can pick a character instead of
+// tuning each number
+struct PersonalityProfile {
+    name: &'static str,
+    gravity: f32,
+    jump_force: f32,
+    max_speed_x: f32,
+    jump_probability: f32,
+    sleep_after_idle: f32,
+    behavior_mode: BehaviorMode,
+}
+
+const PERSONALITIES: [PersonalityProfile; 6] = [
+    PersonalityProfile {
+        name: "Normal",
+        gravity: 980.0,
+        jump_force: -480.0,
+        max_speed_x: 200.0,
+        jump_probability: 0.15,
+        sleep_after_idle: 15.0,
+        behavior_mode: BehaviorMode::Wander,
+    },
+    PersonalityProfile {
+        name: "Calm",
+        gravity: 900.0,
+        jump_force: -380.0,
+        max_speed_x: 120.0,
+        jump_probability: 0.05,
+        sleep_after_idle: 20.0,
+        behavior_mode: BehaviorMode::Wander,
+    },
+    PersonalityProfile {
+        name: "Hyper",
+        gravity: 1200.0,
+        jump_force: -650.0,
+        max_speed_x: 400.0,
+        jump_probability: 0.4,
+        sleep_after_idle: 60.0,
+        behavior_mode: BehaviorMode::Wander,
+    },
+    PersonalityProfile {
+        name: "Lazy",
+        gravity: 980.0,
+        jump_force: -350.0,
+        max_speed_x: 90.0,
+        jump_probability: 0.02,
+        sleep_after_idle: 5.0,
+        behavior_mode: BehaviorMode::Wander,
+    },
+    PersonalityProfile {
+        name: "Clingy",
+        gravity: 980.0,
+        jump_force: -480.0,
+        max_speed_x: 250.0,
+        jump_probability: 0.15,
+        sleep_after_idle: 30.0,
+        behavior_mode: BehaviorMode::FollowCursor,
+    },
+    PersonalityProfile {
+        name: "Shy",
+        gravity: 980.0,
+        jump_force: -520.0,
+        max_speed_x: 300.0,
+        jump_probability: 0.1,
+        sleep_after_idle: 15.0,
+        behavior_mode: BehaviorMode::FleeCursor,
+    },
+];
+
+// A part of the window the pet is penned into, in window coordinates
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Confinement {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DragState {
+    offset_x: f32, // cursor position relative to the pet's top-left corner
+    offset_y: f32,
+    velocity_x: f32,
+    velocity_y: f32,
+}
+
+impl DragState {
+    fn new() -> Self {
+        Self {
+            offset_x: 0.0,
+            offset_y: 0.0,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct SaveData {
     selected_pet: String,
@@ -103,27 +959,105 @@ struct SaveData {
     red_panda: PetNeeds,
 }
 
-#[derive(Debug, Clone, Copy)]
+// Missing fields in a saved pet fall back to a fresh pet's values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 struct PetState {
+    id: usize,
     x: f32,
     y: f32,
     velocity_x: f32,
     velocity_y: f32,
-    last_update: Instant,
+    // None until the first update, which then steps by zero instead of by
+    // however long the pet sat around before anyone asked for a frame
+    #[serde(skip)]
+    last_update: Option<Instant>,
     is_on_ground: bool,
+    is_on_ceiling: bool, // hanging from the top edge, gravity is off until it lets go
+    jumps_remaining: u8,
     window_width: f32,
     window_height: f32,
+    pet_width: f32,
+    pet_height: f32,
     animation_state: AnimationState,
     facing_direction: bool, // true for right, false for left
     idle_timer: f32,       // how long we've been idle
     idle_duration: f32,    // how long to stay idle before moving
     action_timer: f32,     // how long current walk/run action lasts
     current_action: PetAction,
+    walk_target_x: Option<f32>,
     love_timer: f32,
     needs: PetNeeds,
+    stamina: f32,
+    physics: PhysicsConfig,
+    behavior_mode: BehaviorMode,
+    behavior: BehaviorConfig,
+    confinement: Option<Confinement>,
+    home: Option<(f32, f32)>, // where the pet's centre heads back to when idle, in window coordinates
+    #[serde(skip)]
+    attractor: Option<Attractor>, // in window coordinates
+    skin: String, // which sprite sheet the frontend draws, empty for its default
+    #[serde(skip)]
+    idle_since: Option<Instant>,
+    #[serde(skip)]
+    landing_until: Option<Instant>,
+    #[serde(skip)]
+    ground_since: Option<Instant>, // when the pet last touched down
+    #[serde(skip)]
+    reaction_until: Option<Instant>,
+    #[serde(skip)]
+    startled_until: Option<Instant>,
+    #[serde(skip)]
+    last_startle: Option<Instant>,
+    #[serde(skip)]
+    forced_animation: Option<(AnimationState, Instant)>, // force_animation's state and when it runs out
+    #[serde(skip)]
+    facing_override: Option<bool>, // set_facing while moving, applied once the pet idles
+    #[serde(skip)]
+    events: Vec<PhysicsEvent>,
+    #[serde(skip)]
+    last_landing_event: Option<Instant>,
+    #[serde(skip)]
+    on_ground_last_update: bool, // so a take-off between updates still counts as a jump
+    #[serde(skip)]
+    perimeter_corner: Option<bool>, // reached the end of its edge walking right (or left), update turns it
+    #[serde(skip)]
+    breathing_timer: f32,
+    #[serde(skip)]
+    burst_timer: f32, // seconds left of the current burst of energy
+    // Playback rate the frontend should use for the current animation
+    #[serde(skip)]
+    animation_speed: f32,
+    // Squash-and-stretch for the renderer, 1.0 is the sprite's normal shape
+    #[serde(skip)]
+    scale_x: f32,
+    #[serde(skip)]
+    scale_y: f32,
+    #[serde(skip)]
+    opacity: f32, // for the renderer, drops while the pet sits idle
+    // Gusty wind: the current and the target multiplier on the set wind
+    #[serde(skip)]
+    gust: f32,
+    #[serde(skip)]
+    gust_target: f32,
+    // Debug counters for lining frontend stutters up with physics steps
+    #[serde(skip)]
+    frame_count: u64,
+    #[serde(skip)]
+    sim_time: f32,
+    // Where the pet was on the last few updates, newest at the back
+    #[serde(skip)]
+    trail: VecDeque<(f32, f32)>,
+    #[serde(skip)]
+    is_grabbed: bool,
+    #[serde(skip, default = "DragState::new")]
+    drag: DragState,
+    // All of the pet's randomness comes from here, so a fixed seed replays it exactly
+    #[serde(skip, default = "StdRng::from_entropy")]
+    rng: StdRng,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum PetAction {
     Idling,
     Walking,
@@ -131,8 +1065,18 @@ enum PetAction {
     Sleeping,
 }
 
+impl Default for PetState {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT)
+    }
+}
+
 impl PetState {
     fn new(window_width: f32, window_height: f32) -> Self {
+        Self::with_seed(rand::random(), window_width, window_height)
+    }
+
+    fn with_seed(seed: u64, window_width: f32, window_height: f32) -> Self {
         // Use sensible defaults for initial window size from config (400x300)
         let effective_width = if window_width <= 0.0 {
             DEFAULT_WINDOW_WIDTH
@@ -150,29 +1094,152 @@ impl PetState {
             effective_width, effective_height
         );
 
-        PetState {
-            x: effective_width / 2.0 - PET_WIDTH / 2.0,
-            y: effective_height - PET_HEIGHT,
+        let mut pet = PetState {
+            id: 0,
+            x: 0.0,
+            y: 0.0,
             velocity_x: 0.0,
             velocity_y: 0.0,
-            last_update: Instant::now(),
+            last_update: None,
             is_on_ground: true,
+            is_on_ceiling: false,
+            jumps_remaining: MAX_JUMPS,
             window_width: effective_width,
             window_height: effective_height,
+            pet_width: PET_WIDTH,
+            pet_height: PET_HEIGHT,
             animation_state: AnimationState::IdleRight,
             facing_direction: true,
             idle_timer: 0.0,
             idle_duration: 2.0,   // start with a 2 second idle
             action_timer: 0.0,
             current_action: PetAction::Idling,
+            walk_target_x: None,
             love_timer: 0.0,
             needs: PetNeeds::new(),
+            stamina: MAX_STAMINA,
+            physics: PhysicsConfig::new(),
+            behavior_mode: BehaviorMode::Wander,
+            behavior: BehaviorConfig::new(),
+            confinement: None,
+            home: None,
+            attractor: None,
+            skin: String::new(),
+            idle_since: None,
+            landing_until: None,
+            reaction_until: None,
+            startled_until: None,
+            last_startle: None,
+            forced_animation: None,
+            ground_since: None,
+            facing_override: None,
+            events: Vec::new(),
+            last_landing_event: None,
+            on_ground_last_update: false,
+            perimeter_corner: None,
+            breathing_timer: 0.0,
+            burst_timer: 0.0,
+            animation_speed: 1.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            opacity: 1.0,
+            gust: 1.0,
+            gust_target: 1.0,
+            frame_count: 0,
+            sim_time: 0.0,
+            trail: VecDeque::with_capacity(TRAIL_CAPACITY),
+            is_grabbed: false,
+            drag: DragState::new(),
+            rng: StdRng::seed_from_u64(seed),
+        };
+
+        pet.move_to_spawn();
+        pet
+    }
+
+    // Centre the pet on the floor
+    fn move_to_spawn(&mut self) {
+        self.shrink_to_fit();
+        self.x = (self.window_width / 2.0 - self.pet_width / 2.0).max(0.0);
+
+        if self.physics.spawn_from_top {
+            self.y = 0.0;
+            self.velocity_y = SPAWN_DROP_SPEED;
+            self.is_on_ground = false;
+        } else {
+            self.y = (self.window_height - self.pet_height).max(0.0);
+        }
+    }
+
+    // Extra pets start out like the primary one: same tuning, size,
+    // confinement, home and attractor, only shrunk if their window is too
+    // small for it
+    fn take_tuning_from(&mut self, primary: &PetState) {
+        self.physics = primary.physics;
+        self.behavior_mode = primary.behavior_mode;
+        self.behavior = primary.behavior;
+        self.pet_width = primary.pet_width;
+        self.pet_height = primary.pet_height;
+        self.confinement = primary.confinement;
+        self.home = primary.home;
+        self.attractor = primary.attractor;
+        self.shrink_to_fit();
+    }
+
+    // Stand in the `index`th of `count` evenly spaced spots along the floor
+    fn spread_along_floor(&mut self, index: usize, count: usize) {
+        if count > 1 {
+            let spacing = self.window_width / count as f32;
+            let center = spacing * (index as f32 + 0.5);
+            self.x = (center - self.pet_width / 2.0).clamp(0.0, (self.window_width - self.pet_width).max(0.0));
+        }
+    }
+
+    // A pet bigger than its window would spawn half off-screen, scale it down instead
+    fn shrink_to_fit(&mut self) {
+        if self.pet_width <= self.window_width && self.pet_height <= self.window_height {
+            return;
+        }
+
+        if self.window_width < MIN_PET_SIZE || self.window_height < MIN_PET_SIZE {
+            println!(
+                "Error: window {}x{} is too small for a pet (minimum {}px)",
+                self.window_width, self.window_height, MIN_PET_SIZE
+            );
         }
+
+        self.pet_width = self.pet_width.min(self.window_width).max(MIN_PET_SIZE);
+        self.pet_height = self.pet_height.min(self.window_height).max(MIN_PET_SIZE);
+
+        println!(
+            "Pet does not fit in a {}x{} window, shrinking it to {}x{}",
+            self.window_width, self.window_height, self.pet_width, self.pet_height
+        );
+    }
+
+    // Rebuild the pet from scratch, keeping its id, size and tuning
+    fn reset(&mut self, window_width: f32, window_height: f32) {
+        let mut fresh = PetState::new(window_width, window_height);
+
+        fresh.id = self.id;
+        fresh.pet_width = self.pet_width;
+        fresh.pet_height = self.pet_height;
+        fresh.physics = self.physics;
+        fresh.behavior_mode = self.behavior_mode;
+        fresh.behavior = self.behavior;
+        fresh.confinement = self.confinement;
+        fresh.home = self.home;
+        fresh.skin = self.skin.clone();
+        fresh.rng = self.rng.clone();
+        fresh.frame_count = self.frame_count;
+        fresh.sim_time = self.sim_time;
+        fresh.move_to_spawn();
+
+        *self = fresh;
     }
 
     fn choose_idle_animation(&mut self) {
-        let mut rng = rand::thread_rng();
-        let roll: f32 = rng.gen();
+        let roll: f32 = self.rng.gen();
 
         let affection = self.needs.affection;
 
@@ -205,13 +1272,64 @@ impl PetState {
         };
     }
 
+    fn jump(&mut self) {
+        if self.jumps_remaining == 0 || self.behavior.reduced_motion {
+            return;
+        }
+
+        self.jumps_remaining -= 1;
+        self.stamina = (self.stamina - STAMINA_PER_JUMP).max(0.0);
+        self.velocity_y = self.physics.jump_force;
+        self.is_on_ground = false;
+    }
+
+    // Manual control: head left (-1) or right (+1) at full speed
+    fn steer(&mut self, direction: f32) {
+        self.wake();
+        self.current_action = PetAction::Idling;
+        self.walk_target_x = None;
+        self.facing_direction = direction > 0.0;
+        self.velocity_x = self.physics.max_speed_x.copysign(direction);
+    }
+
+    fn fall_asleep(&mut self) {
+
+        self.current_action = PetAction::Sleeping;
+        self.action_timer = self.rng.gen_range(20.0..30.0);
+
+        self.velocity_x = 0.0;
+
+        self.animation_state = if self.facing_direction {
+            AnimationState::SleepingRight
+        } else {
+            AnimationState::SleepingLeft
+        };
+    }
+
+    fn wake(&mut self) {
+        if self.current_action != PetAction::Sleeping {
+            return;
+        }
+
+        self.current_action = PetAction::Idling;
+        self.idle_timer = 0.0;
+        self.idle_since = None;
+        self.choose_idle_animation();
+    }
+
     fn is_cursor_over_pet(&self, cursor_x: f32, cursor_y: f32) -> bool {
     const HITBOX_PADDING: f32 = 8.0;
 
-    let left = self.x + HITBOX_PADDING;
-    let right = self.x + PET_WIDTH - HITBOX_PADDING;
-    let top = self.y + HITBOX_PADDING;
-    let bottom = self.y + PET_HEIGHT - HITBOX_PADDING;
+    let (width, height) = self.screen_size();
+
+    // Keep tiny pets clickable
+    let padding_x = HITBOX_PADDING.min(width / 4.0);
+    let padding_y = HITBOX_PADDING.min(height / 4.0);
+
+    let left = self.x + padding_x;
+    let right = self.x + width - padding_x;
+    let top = self.y + padding_y;
+    let bottom = self.y + height - padding_y;
 
     cursor_x >= left
         && cursor_x <= right
@@ -219,10 +1337,47 @@ impl PetState {
         && cursor_y <= bottom
     }
 
-    fn emotion_state(&self) -> EmotionState {
-        if self.needs.affection < 25.0 {
-            EmotionState::Lonely
-        } else if self.needs.affection < 50.0 {
+    fn snapshot(&self) -> PetSnapshot {
+        let (width, height) = self.screen_size();
+
+        PetSnapshot {
+            id: self.id,
+            x: self.x,
+            y: self.y,
+            velocity_x: self.velocity_x,
+            velocity_y: self.velocity_y,
+            is_on_ground: self.is_on_ground,
+            facing_direction: self.facing_direction,
+            width,
+            height,
+            animation: self.animation_state,
+            stamina: self.stamina,
+            gravity: self.physics.gravity_direction,
+            skin: self.skin.clone(),
+            animation_speed: self.animation_speed,
+            scale_x: self.scale_x,
+            scale_y: self.scale_y,
+            frame_count: self.frame_count,
+            sim_time: self.sim_time,
+            opacity: self.opacity,
+        }
+    }
+
+    fn gain_affection(&mut self) {
+        let affection_gain = match self.emotion_state() {
+            EmotionState::Lonely => 8.0,
+            EmotionState::Neutral => 5.0,
+            EmotionState::Happy => 3.0,
+            EmotionState::Excited => 1.5,
+        };
+
+        self.needs.affection = (self.needs.affection + affection_gain).min(100.0);
+    }
+
+    fn emotion_state(&self) -> EmotionState {
+        if self.needs.affection < 25.0 {
+            EmotionState::Lonely
+        } else if self.needs.affection < 50.0 {
             EmotionState::Neutral
         } else if self.needs.affection < 75.0 {
             EmotionState::Happy
@@ -231,194 +1386,293 @@ impl PetState {
         }
     }
 
-    fn update(&mut self, window_width: f32, window_height: f32) {
-        if (self.window_width - window_width).abs() > 1.0
-            || (self.window_height - window_height).abs() > 1.0
-        {
-            self.window_width = window_width;
-            self.window_height = window_height;
-        }
-
-        let now = Instant::now();
-        let mut delta_time = now.duration_since(self.last_update).as_secs_f32();
-        self.last_update = now;
-        delta_time = delta_time.min(0.05);
-
-        const AFFECTION_DECAY_PER_SECOND: f32 = 1.0;
-        const ENERGY_DECAY_PER_SECOND: f32 = 0.0005;
+    fn grab(&mut self, cursor_x: f32, cursor_y: f32) {
+        self.is_grabbed = true;
+        self.drag = DragState {
+            offset_x: cursor_x - self.x,
+            offset_y: cursor_y - self.y,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+        };
+        self.velocity_x = 0.0;
+        self.velocity_y = 0.0;
+        self.is_on_ground = false;
+        self.is_on_ceiling = false;
+        self.love_timer = 0.0;
+        self.current_action = PetAction::Idling;
+    }
 
-        if self.love_timer <= 0.0 {
-            self.needs.affection =
-                (self.needs.affection - AFFECTION_DECAY_PER_SECOND * delta_time)
-                    .max(0.0);
+    fn release(&mut self) {
+        if !self.is_grabbed {
+            return;
         }
 
-        if self.current_action != PetAction::Sleeping {
-            self.needs.energy =
-                (self.needs.energy - ENERGY_DECAY_PER_SECOND * delta_time).max(0.0);
+        self.is_grabbed = false;
+        // Throw the pet with the smoothed velocity of the last few cursor moves
+        self.velocity_x = self.drag.velocity_x.clamp(-MAX_THROW_SPEED, MAX_THROW_SPEED);
+        self.velocity_y = self.drag.velocity_y.clamp(-MAX_THROW_SPEED, MAX_THROW_SPEED);
+        if self.velocity_x.abs() > 1.0 {
+            self.facing_direction = self.velocity_x > 0.0;
         }
+        self.drag = DragState::new();
+    }
 
-        if self.love_timer > 0.0 {
-            self.love_timer -= delta_time;
+    // Just let go of with a hard throw down through the floor
+    fn was_flung_off_screen(&mut self) -> bool {
+        let (window_width, window_height) = (self.window_width, self.window_height);
+        self.with_gravity_frame(window_width, window_height, |pet| {
+            pet.velocity_y >= DISMISS_THROW_SPEED
+                && pet.y + pet.pet_height >= pet.window_height - DISMISS_FLOOR_MARGIN
+        })
+    }
 
-            self.velocity_x = 0.0;
+    fn follow_cursor(&mut self, cursor: Option<(f32, f32)>, delta_time: f32) {
+        if let Some((cursor_x, cursor_y)) = cursor {
+            let max_x = (self.window_width - self.pet_width).max(0.0);
+            let max_y = (self.window_height - self.pet_height).max(0.0);
+            let target_x = (cursor_x - self.drag.offset_x).clamp(0.0, max_x);
+            let target_y = (cursor_y - self.drag.offset_y).clamp(0.0, max_y);
 
-            self.current_action = PetAction::Idling;
+            if delta_time > 0.0 {
+                let sample_vx = (target_x - self.x) / delta_time;
+                let sample_vy = (target_y - self.y) / delta_time;
+                self.drag.velocity_x += (sample_vx - self.drag.velocity_x) * DRAG_SMOOTHING;
+                self.drag.velocity_y += (sample_vy - self.drag.velocity_y) * DRAG_SMOOTHING;
+            }
 
-            return;
+            self.x = target_x;
+            self.y = target_y;
         }
 
-        const GRAVITY: f32 = 980.0;
-        const JUMP_FORCE: f32 = -480.0;
-        const WALK_SPEED: f32 = 80.0;
-        const RUN_SPEED: f32 = 200.0;
-        const FRICTION: f32 = 6.0;        // ground deceleration multiplier
-        const MOVEMENT_THRESHOLD: f32 = 8.0;
+        self.velocity_x = 0.0;
+        self.velocity_y = 0.0;
+        self.is_on_ground = false;
+        self.animation_state = if self.facing_direction {
+            AnimationState::FallingRight
+        } else {
+            AnimationState::FallingLeft
+        };
+    }
 
-        let effective_width = if window_width <= 10.0 { DEFAULT_WINDOW_WIDTH } else { window_width };
-        let effective_height = if window_height <= 10.0 { DEFAULT_WINDOW_HEIGHT } else { window_height };
+    // Pull the pet straight into a window resized from outside the physics
+    fn fit_to_window(&mut self, window_width: f32, window_height: f32) {
+        self.window_width = window_width;
+        self.window_height = window_height;
 
-        // --- Gravity ---
-        if !self.is_on_ground {
-            self.velocity_y += GRAVITY * delta_time;
-        }
+        let (width, height) = self.screen_size();
+        self.x = self.x.clamp(0.0, (window_width - width).max(0.0));
+        self.y = self.y.clamp(0.0, (window_height - height).max(0.0));
+    }
+
+    // Jump straight to a spot in the window. Unlike reset this keeps the
+    // rest of the pet's state; it just stops moving and falls from there.
+    fn teleport(&mut self, x: f32, y: f32) {
+        let gravity = self.physics.gravity_direction;
+        let (window_width, window_height) = (self.window_width, self.window_height);
+        let (x, y) = gravity.box_to_local(
+            x,
+            y,
+            (window_width, window_height),
+            (self.pet_width, self.pet_height),
+        );
 
-        let mut rng = rand::thread_rng();
+        self.with_gravity_frame(window_width, window_height, |pet| pet.teleport_local(x, y));
+    }
+
+    fn teleport_local(&mut self, x: f32, y: f32) {
+        let right_boundary = (self.window_width - self.pet_width).max(0.0);
+        let floor = (self.window_height - self.pet_height).max(0.0);
 
-        // --- Ground behaviour state machine ---
+        self.x = x.clamp(0.0, right_boundary);
+        self.y = y.clamp(0.0, floor);
+        self.velocity_x = 0.0;
+        self.velocity_y = 0.0;
+        self.is_on_ground = self.y >= floor;
+        self.is_on_ceiling = false;
         if self.is_on_ground {
-            match self.current_action {
-                PetAction::Idling => {
-                    // Apply friction to bleed off any residual velocity
-                    self.velocity_x *= (1.0 - FRICTION * delta_time).max(0.0);
+            self.jumps_remaining = MAX_JUMPS;
+        }
+        self.current_action = PetAction::Idling;
+        self.walk_target_x = None;
+        self.idle_timer = 0.0;
+    }
 
-                    self.idle_timer += delta_time;
-                    if self.idle_timer >= self.idle_duration {
-                        // Decide next action
-                        self.idle_timer = 0.0;
+    fn climb_to_ceiling(&mut self) {
+        self.is_on_ceiling = true;
+        self.is_on_ground = false;
+        self.y = 0.0;
+        self.velocity_y = 0.0;
+        self.current_action = PetAction::Walking;
+        self.walk_target_x = None;
+        self.facing_direction = self.rng.gen_bool(0.5);
+        // Hang around up there for a while before dropping
+        self.action_timer = self.rng.gen_range(3.0..8.0);
+    }
 
-                        let mut sleep_chance: f32 = match self.emotion_state() {
-                            EmotionState::Lonely => 0.15,
-                            EmotionState::Neutral => 0.10,
-                            EmotionState::Happy => 0.07,
-                            EmotionState::Excited => 0.05,
-                        };
-                    
-                        if self.needs.energy < 25.0 {
-                            sleep_chance += 0.15;
-                        } else if self.needs.energy < 50.0 {
-                            sleep_chance += 0.08;
-                        }
+    fn walk_on_ceiling(&mut self, delta_time: f32, walk_speed: f32, effective_width: f32) {
+        // Turn around at the corners instead of bouncing off
+        let right_boundary = effective_width - self.pet_width;
+        if self.x <= 0.0 {
+            self.facing_direction = true;
+        } else if self.x >= right_boundary {
+            self.facing_direction = false;
+        }
 
-                        sleep_chance = sleep_chance.min(0.30);
+        self.y = 0.0;
+        self.velocity_y = 0.0;
+        self.velocity_x = if self.facing_direction { walk_speed } else { -walk_speed };
 
-                        let roll: f32 = rng.gen();
+        self.action_timer -= delta_time;
+        if self.action_timer <= 0.0 {
+            // Let go, gravity takes over and the pet falls back to the floor
+            self.is_on_ceiling = false;
+            self.current_action = PetAction::Idling;
+            self.idle_timer = 0.0;
+        }
+    }
 
-                        if roll < sleep_chance{
-                            self.current_action = PetAction::Sleeping;
-                            self.action_timer = rng.gen_range(20.0..30.0);
+    // Wander-target mode: head for a random spot with bounded acceleration,
+    // easing off on the way in, then wait a moment before picking the next
+    fn seek_target(&mut self, delta_time: f32, effective_width: f32) {
+        let Some(target_x) = self.walk_target_x else {
+            self.velocity_x *= (-self.physics.ground_friction * delta_time).exp();
+            self.idle_timer += delta_time;
+            if self.idle_timer >= self.behavior.target_pause {
+                let right_boundary = (effective_width - self.pet_width).max(0.0);
+                self.walk_target_x = Some(self.rng.gen_range(0.0..=right_boundary));
+                self.current_action = PetAction::Walking;
+                self.idle_timer = 0.0;
+            }
+            return;
+        };
 
-                            self.velocity_x = 0.0;
+        let dx = target_x - self.x;
+        if dx.abs() <= WALK_ARRIVE_DISTANCE {
+            self.walk_target_x = None;
+            self.current_action = PetAction::Idling;
+            return;
+        }
 
-                            self.animation_state = if self.facing_direction {
-                                AnimationState::SleepingRight
-                            } else {
-                                AnimationState:: SleepingLeft
-                            };
-                        } 
-                        else if roll < 0.20 {
-                            // Jump
-                            self.velocity_y = JUMP_FORCE;
-                            let speed = rng.gen_range(60.0..RUN_SPEED);
-                            self.velocity_x = if self.facing_direction { speed } else { -speed };
-                            self.is_on_ground = false;
-                            self.current_action = PetAction::Idling; // reset after landing
-                        } else if roll < 0.55 {
-                            // Walk
-                            self.current_action = PetAction::Walking;
-                            self.action_timer = rng.gen_range(1.5..4.0);
-                            // Randomly pick a direction
-                            self.facing_direction = rng.gen_bool(0.5);
-                        } else {
-                            // Run
-                            self.current_action = PetAction::Running;
-                            self.action_timer = rng.gen_range(0.8..2.5);
-                            self.facing_direction = rng.gen_bool(0.5);
-                        }
-                        // Next idle will last 1–4 seconds
-                        self.idle_duration = rng.gen_range(1.0..4.0);
-                    }
-                }
+        let arrive_radius = self.behavior.target_arrive_radius.max(WALK_ARRIVE_DISTANCE);
+        let desired_vx = self.physics.max_speed_x.copysign(dx) * (dx.abs() / arrive_radius).min(1.0);
+        let max_change = self.behavior.target_acceleration * delta_time;
+        self.velocity_x += (desired_vx - self.velocity_x).clamp(-max_change, max_change);
+        self.facing_direction = dx > 0.0;
+    }
 
-                PetAction::Walking => {
-                    let target_vx = if self.facing_direction { WALK_SPEED } else { -WALK_SPEED };
-                    // Smoothly accelerate toward walk speed
-                    self.velocity_x += (target_vx - self.velocity_x) * (FRICTION * delta_time).min(1.0);
+    fn walk_to(&mut self, target_x: f32, walk_speed: f32) {
+        self.current_action = PetAction::Walking;
+        self.walk_target_x = Some(target_x);
+        // Safety timeout in case a wall or the cursor gets in the way
+        self.action_timer = (target_x - self.x).abs() / walk_speed + 2.0;
+        self.facing_direction = target_x > self.x;
+    }
 
-                    self.action_timer -= delta_time;
-                    if self.action_timer <= 0.0 {
-                        self.current_action = PetAction::Idling;
-                        self.idle_timer = 0.0;
-                        self.choose_idle_animation();
-                    }
-                }   
+    // Nothing is moving or about to, so the physics can tick slowly
+    fn is_resting(&self) -> bool {
+        self.is_on_ground
+            && !self.is_grabbed
+            && self.landing_until.is_none()
+            && self.velocity_x.abs() < IDLE_SPEED_THRESHOLD
+            && matches!(self.current_action, PetAction::Idling | PetAction::Sleeping)
+    }
 
-                PetAction::Running => {
-                    let target_vx = if self.facing_direction { RUN_SPEED } else { -RUN_SPEED };
-                    // Faster acceleration for running
-                    self.velocity_x += (target_vx - self.velocity_x) * (FRICTION * 1.5 * delta_time).min(1.0);
+    // Push the pet and let the physics take it from there
+    fn apply_impulse(&mut self, dx: f32, dy: f32) {
+        self.wake();
+        self.current_action = PetAction::Idling;
+        self.walk_target_x = None;
+        self.is_on_ceiling = false;
 
-                    self.action_timer -= delta_time;
-                    if self.action_timer <= 0.0 {
-                        self.current_action = PetAction::Idling;
-                        self.idle_timer = 0.0;
-                        self.choose_idle_animation();
-                    }
-                }
+        self.velocity_x = (self.velocity_x + dx).clamp(-MAX_THROW_SPEED, MAX_THROW_SPEED);
+        self.velocity_y = (self.velocity_y + dy).clamp(-MAX_THROW_SPEED, MAX_THROW_SPEED);
 
-                PetAction::Sleeping => {
+        // "Up" is away from whichever edge the pet stands on
+        let gravity = self.physics.gravity_direction;
+        let (local_velocity_x, local_velocity_y) = gravity.vector_to_local(self.velocity_x, self.velocity_y);
+        if self.behavior.reduced_motion {
+            // Only a gentle nudge along the floor, never up into the air
+            (self.velocity_x, self.velocity_y) = gravity.vector_to_screen(
+                local_velocity_x.clamp(-REDUCED_MOTION_SPEED, REDUCED_MOTION_SPEED),
+                local_velocity_y.max(0.0),
+            );
+            return;
+        }
+        if local_velocity_y < 0.0 {
+            self.is_on_ground = false;
+        }
+    }
 
-                    const ENERGY_RECOVERY_PER_SECOND: f32 = 0.5;
+    // Leap up in fright. Returns false, and does nothing, while still
+    // recovering from the last scare so rapid triggers don't make it jitter.
+    fn startle(&mut self, now: Instant) -> bool {
+        let recovered = self
+            .last_startle
+            .is_none_or(|last| now.duration_since(last).as_secs_f32() >= STARTLE_COOLDOWN);
+        if !recovered {
+            return false;
+        }
 
-                    self.needs.energy = 
-                    (self.needs.energy + ENERGY_RECOVERY_PER_SECOND * delta_time).min(100.0);
+        self.last_startle = Some(now);
+        self.startled_until = Some(now + std::time::Duration::from_secs_f32(STARTLE_DURATION));
+        if self.behavior.reduced_motion {
+            // Only the startled pose, no leap
+            self.wake();
+        } else if self.is_on_ground || self.is_on_ceiling {
+            let (dx, dy) = self
+                .physics
+                .gravity_direction
+                .vector_to_screen(0.0, self.physics.jump_force * STARTLE_JUMP);
+            self.apply_impulse(dx, dy);
+        } else {
+            self.wake();
+        }
+        true
+    }
 
-                    self.velocity_x = 0.0;
+    fn clamp_to_bounds(&mut self, effective_width: f32, effective_height: f32) {
+        let right_boundary = (effective_width - self.pet_width).max(0.0);
+        let floor = (effective_height - self.pet_height).max(0.0);
 
-                    self.action_timer -= delta_time;
+        self.x = self.x.clamp(0.0, right_boundary);
+        self.y = self.y.clamp(0.0, floor);
+    }
 
-                    self.animation_state = if self.facing_direction {
-                        AnimationState::SleepingRight
-                    } else {
-                        AnimationState::SleepingLeft
-                    };
+    // Returns the impact speed if the pet touched down during this step
+    fn integrate(&mut self, dt: f32, effective_width: f32, effective_height: f32) -> Option<f32> {
+        let mut landed = None;
 
-                    if self.action_timer <= 0.0 {
-                        self.current_action = PetAction::Idling;
-                        self.idle_timer = 0.0;
-                        self.idle_duration = rng.gen_range(1.0..4.0);
-                    }
-                }
-            }
+        // --- Gravity and air drag ---
+        if !self.is_on_ground && !self.is_on_ceiling {
+            self.velocity_y += self.physics.gravity * dt;
+            self.velocity_y = self.velocity_y.min(self.physics.terminal_velocity);
+            self.velocity_x -= self.velocity_x * self.physics.air_drag * dt;
         }
 
         // --- Position update ---
-        self.x += self.velocity_x * delta_time;
-        self.y += self.velocity_y * delta_time;
+        self.x += self.velocity_x * dt;
+        self.y += self.velocity_y * dt;
 
         // --- Boundaries ---
-        let floor = effective_height - PET_HEIGHT;
-        if self.y >= floor {
+        let floor = effective_height - self.pet_height;
+        let bounce_speed = self.velocity_y * self.physics.restitution_floor;
+        if self.y >= floor && !self.is_on_ground && bounce_speed > self.physics.bounce_threshold {
+            // Bounce back up instead of sticking the landing
+            self.y = floor;
+            self.velocity_y = -bounce_speed;
+        } else if self.y >= floor {
             self.y = floor;
-            self.velocity_y = 0.0;
             if !self.is_on_ground {
                 // Just landed — go idle briefly
+                landed = Some(self.velocity_y);
                 self.is_on_ground = true;
+                self.jumps_remaining = MAX_JUMPS;
                 self.current_action = PetAction::Idling;
                 self.idle_timer = 0.0;
-                self.idle_duration = rng.gen_range(0.5..2.0);
+                self.idle_duration = self.rng.gen_range(0.5..2.0);
                 self.choose_idle_animation();
             }
+            self.velocity_y = 0.0;
         }
 
         if self.y < 0.0 {
@@ -428,7 +1682,7 @@ impl PetState {
 
         if self.x < 0.0 {
             self.x = 0.0;
-            self.velocity_x = self.velocity_x.abs() * 0.5;
+            self.velocity_x = self.velocity_x.abs() * self.physics.restitution_left;
             self.facing_direction = true;
             if self.current_action != PetAction::Idling {
                 // Reverse direction instead of stopping
@@ -436,199 +1690,3527 @@ impl PetState {
             }
         }
 
-        let right_boundary = effective_width - PET_WIDTH;
+        let right_boundary = effective_width - self.pet_width;
         if self.x > right_boundary {
             self.x = right_boundary;
-            self.velocity_x = - self.velocity_x.abs() * 0.5;
+            self.velocity_x = - self.velocity_x.abs() * self.physics.restitution_right;
             self.facing_direction = false;
         }
 
-        // --- Animation state ---
-        if self.current_action == PetAction::Sleeping {
-            self.animation_state = if self.facing_direction {
-                AnimationState::SleepingRight
-            } else {
-                AnimationState::SleepingLeft
-            };
-            
-        }else if !self.is_on_ground {
-            self.animation_state = if self.velocity_y < 0.0 {
-                if self.facing_direction { AnimationState::JumpingRight } else { AnimationState::JumpingLeft }
-            } else {
-                if self.facing_direction { AnimationState::FallingRight } else { AnimationState::FallingLeft }
-            };
-        } else if self.velocity_x.abs() > RUN_SPEED * 0.6 {
-            self.animation_state = if self.velocity_x > 0.0 { AnimationState::RunningRight } else { AnimationState::RunningLeft };
-        } else if self.velocity_x.abs() > MOVEMENT_THRESHOLD {
-            self.animation_state = if self.velocity_x > 0.0 { AnimationState::RunningRight } else { AnimationState::RunningLeft };
+        landed
+    }
+
+    // Screen-space size of the pet's box, which is turned on its side when
+    // gravity pulls towards a wall
+    fn screen_size(&self) -> (f32, f32) {
+        if self.physics.gravity_direction.is_sideways() {
+            (self.pet_height, self.pet_width)
         } else {
-            // While the pet is waiting, occasionally use one of the extra idle variants.
-            // The frontend will fall back to normal idle if the current pet does not define it.
-            let currently_idle = matches!(
-                self.animation_state,
-                AnimationState::IdleRight
-                    | AnimationState::IdleLeft
-                    | AnimationState::IdleAlt1Right
-                    | AnimationState::IdleAlt1Left
-                    | AnimationState::IdleAlt2Right
-                    | AnimationState::IdleAlt2Left
+            (self.pet_width, self.pet_height)
+        }
+    }
+
+    // Run `f` with the pet moved into the gravity-down local frame for its
+    // current gravity direction. Outside of this the pet is in screen space.
+    fn with_gravity_frame<R>(
+        &mut self,
+        window_width: f32,
+        window_height: f32,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let gravity = self.physics.gravity_direction;
+        let window = (window_width, window_height);
+        let size = (self.pet_width, self.pet_height);
+
+        // The grab offset is relative to the box corner, which moves to a
+        // different corner in the local frame, so go via the grabbed point
+        let (grab_x, grab_y) = gravity.point_to_local(
+            self.x + self.drag.offset_x,
+            self.y + self.drag.offset_y,
+            window,
+        );
+        (self.x, self.y) = gravity.box_to_local(self.x, self.y, window, size);
+        (self.drag.offset_x, self.drag.offset_y) = (grab_x - self.x, grab_y - self.y);
+        (self.velocity_x, self.velocity_y) = gravity.vector_to_local(self.velocity_x, self.velocity_y);
+        (self.drag.velocity_x, self.drag.velocity_y) =
+            gravity.vector_to_local(self.drag.velocity_x, self.drag.velocity_y);
+        (self.window_width, self.window_height) =
+            gravity.local_window(self.window_width, self.window_height);
+
+        let result = f(self);
+
+        let (grab_x, grab_y) = gravity.point_to_screen(
+            self.x + self.drag.offset_x,
+            self.y + self.drag.offset_y,
+            window,
+        );
+        (self.x, self.y) = gravity.box_to_screen(self.x, self.y, window, size);
+        (self.drag.offset_x, self.drag.offset_y) = (grab_x - self.x, grab_y - self.y);
+        (self.velocity_x, self.velocity_y) = gravity.vector_to_screen(self.velocity_x, self.velocity_y);
+        (self.drag.velocity_x, self.drag.velocity_y) =
+            gravity.vector_to_screen(self.drag.velocity_x, self.drag.velocity_y);
+        (self.window_width, self.window_height) =
+            gravity.local_window(self.window_width, self.window_height);
+
+        result
+    }
+
+    // `now` is passed in rather than read here so the caller controls the clock,
+    // e.g. to step every pet by the same amount or to simulate elapsed time
+    fn update(
+        &mut self,
+        window_width: f32,
+        window_height: f32,
+        cursor: Option<(f32, f32)>,
+        now: Instant,
+    ) {
+        let (origin_x, origin_y, area_width, area_height) = self.play_area(window_width, window_height);
+        self.x -= origin_x;
+        self.y -= origin_y;
+
+        let gravity = self.physics.gravity_direction;
+        let cursor = cursor.map(|(cursor_x, cursor_y)| {
+            gravity.point_to_local(cursor_x - origin_x, cursor_y - origin_y, (area_width, area_height))
+        });
+        let home = self.home.map(|(home_x, home_y)| {
+            gravity.point_to_local(home_x - origin_x, home_y - origin_y, (area_width, area_height))
+        });
+        let attractor = self.attractor.map(|attractor| {
+            let (x, y) = gravity.point_to_local(
+                attractor.x - origin_x,
+                attractor.y - origin_y,
+                (area_width, area_height),
             );
+            Attractor { x, y, ..attractor }
+        });
+        let (local_width, local_height) = gravity.local_window(area_width, area_height);
 
-            if !currently_idle {
-                self.choose_idle_animation();
+        self.with_gravity_frame(area_width, area_height, |pet| {
+            pet.step(local_width, local_height, cursor, home, attractor, now);
+            pet.update_animation_speed();
+            pet.update_squash_and_stretch();
+        });
+        // After step, which can return early, so this is always the latest
+        self.on_ground_last_update = self.is_on_ground;
+        if let Some(walking_right) = self.perimeter_corner.take() {
+            self.turn_perimeter_corner(walking_right, area_width, area_height);
+        }
+
+        self.x += origin_x;
+        self.y += origin_y;
+        // step sized these to the play area, the rest of the code expects the window
+        self.window_width = window_width;
+        self.window_height = window_height;
+
+        self.recover_from_non_finite();
+
+        if self.trail.len() == TRAIL_CAPACITY {
+            self.trail.pop_front();
+        }
+        self.trail.push_back((self.x, self.y));
+    }
+
+    // NaN fails every boundary check, so a pet that picked one up (from a bad
+    // impulse or config value, say) would vanish for good. Put anything that
+    // isn't a finite number back to a sane value, respawning if the position went.
+    fn recover_from_non_finite(&mut self) {
+        fn reset(value: &mut f32, default: f32) -> bool {
+            let bad = !value.is_finite();
+            if bad {
+                *value = default;
             }
+            bad
+        }
+
+        let needs = PetNeeds::new();
+        let mut recovered = false;
+        for (value, default) in [
+            (&mut self.window_width, DEFAULT_WINDOW_WIDTH),
+            (&mut self.window_height, DEFAULT_WINDOW_HEIGHT),
+            (&mut self.velocity_x, 0.0),
+            (&mut self.velocity_y, 0.0),
+            (&mut self.stamina, MAX_STAMINA),
+            (&mut self.needs.affection, needs.affection),
+            (&mut self.needs.hunger, needs.hunger),
+            (&mut self.needs.energy, needs.energy),
+            (&mut self.gust, 1.0),
+            (&mut self.gust_target, 1.0),
+            (&mut self.animation_speed, 1.0),
+            (&mut self.scale_x, 1.0),
+            (&mut self.scale_y, 1.0),
+            (&mut self.opacity, 1.0),
+        ] {
+            recovered |= reset(value, default);
+        }
+
+        if !self.x.is_finite() || !self.y.is_finite() {
+            self.velocity_x = 0.0;
+            self.velocity_y = 0.0;
+            self.move_to_spawn();
+            recovered = true;
+        }
+
+        if recovered {
+            println!("Pet {} had a non-finite value in its state, reset it", self.id);
         }
     }
-}
 
-fn save_file_path() -> PathBuf {
-    let mut path = dirs::data_local_dir()
-        .unwrap_or_else(|| std::env::current_dir().unwrap());
+    // The last `count` recorded positions, oldest first
+    fn recent_trail(&self, count: usize) -> Vec<(f32, f32)> {
+        let skip = self.trail.len().saturating_sub(count);
+        self.trail.iter().skip(skip).copied().collect()
+    }
 
-    path.push("my-desktop-pet");
-    let _ = fs::create_dir_all(&path);
+    // Staring into a wall looks odd, so a pet next to one turns to face the
+    // middle of the screen. A window too narrow to tell the walls apart is left alone.
+    fn face_away_from_wall(&mut self, effective_width: f32) {
+        let right_boundary = (effective_width - self.pet_width).max(0.0);
+        let near_left = self.x <= WALL_FACING_MARGIN;
+        let near_right = self.x >= right_boundary - WALL_FACING_MARGIN;
 
-    path.push("save.json");
-    path
+        if near_left != near_right {
+            self.facing_direction = near_left;
+        }
+    }
+
+    // Walk along whichever edge is the floor right now. Turning onto the next
+    // edge swaps the gravity frame, so that is left to update.
+    fn patrol_perimeter(&mut self, delta_time: f32, walk_speed: f32, acceleration: f32, effective_width: f32) {
+        if self.current_action == PetAction::Sleeping {
+            self.velocity_x *= (-self.physics.ground_friction * delta_time).exp();
+            return;
+        }
+
+        // Look a whole frame ahead, hitting the wall would turn the pet round
+        let right_boundary = (effective_width - self.pet_width).max(0.0);
+        let reach = WALK_ARRIVE_DISTANCE.max(walk_speed * delta_time);
+        let at_corner = if self.facing_direction {
+            self.x >= right_boundary - reach
+        } else {
+            self.x <= reach
+        };
+        self.perimeter_corner = at_corner.then_some(self.facing_direction);
+
+        let target_vx = if self.facing_direction { walk_speed } else { -walk_speed };
+        self.velocity_x += (target_vx - self.velocity_x) * (acceleration * delta_time).min(1.0);
+    }
+
+    // Stand the pet on the next edge round, at the corner it just reached,
+    // still walking the same way. Positions are relative to the play area.
+    fn turn_perimeter_corner(&mut self, walking_right: bool, area_width: f32, area_height: f32) {
+        let gravity = self.physics.gravity_direction.next_edge(walking_right);
+        let (local_width, local_height) = gravity.local_window(area_width, area_height);
+
+        let local_x = if walking_right { 0.0 } else { (local_width - self.pet_width).max(0.0) };
+        let local_y = (local_height - self.pet_height).max(0.0);
+        let speed = self.velocity_x.abs();
+        let local_vx = if walking_right { speed } else { -speed };
+
+        self.physics.gravity_direction = gravity;
+        (self.x, self.y) =
+            gravity.box_to_screen(local_x, local_y, (area_width, area_height), (self.pet_width, self.pet_height));
+        (self.velocity_x, self.velocity_y) = gravity.vector_to_screen(local_vx, 0.0);
+        self.facing_direction = walking_right;
+        self.is_on_ground = true;
+    }
+
+    // Fade out bit by bit once the pet has been idle for a while, and pop
+    // straight back as soon as it does anything
+    fn update_opacity(&mut self, delta_time: f32, now: Instant) {
+        let idle_for = self.idle_since.map_or(0.0, |since| now.duration_since(since).as_secs_f32());
+
+        if self.behavior.fade_rate > 0.0 && idle_for >= self.behavior.fade_after_idle {
+            self.opacity = (self.opacity - self.behavior.fade_rate * delta_time).max(self.behavior.min_opacity);
+        } else {
+            self.opacity = 1.0;
+        }
+    }
+
+    // Speed the run cycle up or slow it down to match how fast the pet is
+    // going, so its feet don't slide. Everything else plays at its normal rate.
+    fn update_animation_speed(&mut self) {
+        let running = matches!(
+            self.animation_state.facing(true),
+            AnimationState::RunningRight | AnimationState::CeilingRight
+        );
+
+        self.animation_speed = if running {
+            (self.velocity_x.abs() / RUN_CYCLE_SPEED).clamp(MIN_ANIMATION_SPEED, MAX_ANIMATION_SPEED)
+        } else {
+            1.0
+        };
+        if self.behavior.reduced_motion {
+            self.animation_speed *= REDUCED_MOTION_ANIMATION_SPEED;
+        }
+    }
+
+    // Where in the window the pet moves about, as (x, y, width, height).
+    // A confined pet lives in its rectangle as if that were the whole window.
+    // Otherwise it gets the window minus the space kept clear at the edges.
+    fn play_area(&self, window_width: f32, window_height: f32) -> (f32, f32, f32, f32) {
+        match self.confinement {
+            Some(area) => (area.x, area.y, area.width, area.height),
+            None => {
+                let top = self.physics.top_offset;
+                let height = window_height - top - self.physics.ground_offset;
+                (0.0, top, window_width, height.max(self.screen_size().1))
+            }
+        }
+    }
+
+    // Where the pet will be over the next `steps` physics substeps if nothing
+    // but physics acts on it. Runs on a copy, the pet itself is left alone.
+    fn predict_trajectory(&self, steps: u32) -> Vec<(f32, f32)> {
+        let mut ghost = self.clone();
+        let (origin_x, origin_y, area_width, area_height) =
+            ghost.play_area(self.window_width, self.window_height);
+        let gravity = ghost.physics.gravity_direction;
+        let (local_width, local_height) = gravity.local_window(area_width, area_height);
+        let size = (ghost.pet_width, ghost.pet_height);
+
+        ghost.x -= origin_x;
+        ghost.y -= origin_y;
+        ghost.with_gravity_frame(area_width, area_height, |pet| {
+            (0..steps)
+                .map(|_| {
+                    pet.integrate(PHYSICS_SUBSTEP, local_width, local_height);
+                    let (x, y) = gravity.box_to_screen(pet.x, pet.y, (area_width, area_height), size);
+                    (x + origin_x, y + origin_y)
+                })
+                .collect()
+        })
+    }
+
+    // Stretch tall on the way up, squash flat while landing. Width moves the
+    // other way so the sprite keeps roughly the same area.
+    fn update_squash_and_stretch(&mut self) {
+        let deform = if self.behavior.reduced_motion {
+            0.0
+        } else if self.landing_until.is_some() {
+            -MAX_SQUASH
+        } else if !self.is_on_ground && self.velocity_y < 0.0 {
+            (-self.velocity_y / STRETCH_SPEED).min(1.0) * MAX_SQUASH
+        } else {
+            0.0
+        };
+
+        self.scale_y = 1.0 + deform;
+        self.scale_x = 1.0 - deform;
+    }
+
+    // One frame of physics and behaviour, always with gravity pointing down
+    fn step(
+        &mut self,
+        window_width: f32,
+        window_height: f32,
+        cursor: Option<(f32, f32)>,
+        home: Option<(f32, f32)>,
+        attractor: Option<Attractor>,
+        now: Instant,
+    ) {
+        // Only this frame's events are kept, whoever advances the pet reads them
+        self.events.clear();
+        let started_on_ground = self.on_ground_last_update;
+
+        if (self.window_width - window_width).abs() > 1.0
+            || (self.window_height - window_height).abs() > 1.0
+        {
+            self.window_width = window_width;
+            self.window_height = window_height;
+        }
+
+        let effective_width = if window_width <= 10.0 { DEFAULT_WINDOW_WIDTH } else { window_width };
+        let effective_height = if window_height <= 10.0 { DEFAULT_WINDOW_HEIGHT } else { window_height };
+
+        // Pull the pet back inside straight away if the window just shrank
+        // (e.g. a monitor was unplugged) instead of waiting for it to bounce back
+        self.clamp_to_bounds(effective_width, effective_height);
+
+        let mut delta_time = self
+            .last_update
+            .map_or(0.0, |last| now.duration_since(last).as_secs_f32());
+        self.last_update = Some(now);
+        // Cap first so a long stall can't turn into a huge scaled step
+        delta_time = delta_time.min(MAX_DELTA_TIME) * self.physics.time_scale;
+        self.frame_count += 1;
+        self.sim_time += delta_time;
+
+        const AFFECTION_DECAY_PER_SECOND: f32 = 1.0;
+        const ENERGY_DECAY_PER_SECOND: f32 = 0.0005;
+
+        if self.love_timer <= 0.0 {
+            self.needs.affection =
+                (self.needs.affection - AFFECTION_DECAY_PER_SECOND * delta_time)
+                    .max(0.0);
+        }
+
+        if self.current_action != PetAction::Sleeping {
+            self.needs.energy =
+                (self.needs.energy - ENERGY_DECAY_PER_SECOND * delta_time).max(0.0);
+        }
+
+        // --- Bursts of energy ---
+        if self.burst_timer > 0.0 {
+            self.burst_timer -= delta_time;
+        } else if self.behavior_mode == BehaviorMode::Wander
+            && self.rng.gen::<f32>() < chance_this_frame(self.behavior.bursts_per_minute / 60.0, delta_time)
+        {
+            self.burst_timer = self.behavior.burst_duration;
+            println!("Pet {} got a burst of energy", self.id);
+        }
+        let bursting = self.burst_timer > 0.0 && !self.behavior.reduced_motion;
+
+        let resting_on_ground = self.is_on_ground
+            && matches!(self.current_action, PetAction::Idling | PetAction::Sleeping);
+        if resting_on_ground {
+            self.stamina = (self.stamina + STAMINA_REGEN_PER_SECOND * delta_time).min(MAX_STAMINA);
+        }
+
+        // While held the pet just sticks to the cursor, no gravity or behaviour
+        if self.is_grabbed {
+            self.follow_cursor(cursor, delta_time);
+            return;
+        }
+
+        if self.love_timer > 0.0 {
+            self.love_timer -= delta_time;
+
+            self.velocity_x = 0.0;
+
+            self.current_action = PetAction::Idling;
+
+            return;
+        }
+
+        const WALK_SPEED: f32 = 80.0;
+        const FRICTION: f32 = 6.0;        // how quickly walking/running reaches its target speed
+        const MOVEMENT_THRESHOLD: f32 = 8.0;
+        // Once running, the pet keeps the run animation until it is slower than
+        // this, so a slide to a stop doesn't flicker between run and idle
+        const STOP_THRESHOLD: f32 = 3.0;
+
+
+        let pet_center_x = self.x + self.pet_width / 2.0;
+        let pet_center_y = self.y + self.pet_height / 2.0;
+        let cursor_target = match self.behavior_mode {
+            BehaviorMode::Wander
+            | BehaviorMode::ManualControl
+            | BehaviorMode::WanderTarget
+            | BehaviorMode::PerimeterWalk => None,
+            BehaviorMode::FollowCursor => cursor,
+            BehaviorMode::FleeCursor => cursor.filter(|&(cursor_x, cursor_y)| {
+                (cursor_x - pet_center_x).hypot(cursor_y - pet_center_y) < self.behavior.flee_radius
+            }),
+        };
+
+        // --- Ground behaviour: chase/flee the cursor, otherwise wander ---
+        if self.is_on_ceiling {
+            self.walk_on_ceiling(delta_time, WALK_SPEED, effective_width);
+        } else if self.behavior_mode == BehaviorMode::WanderTarget {
+            if self.is_on_ground {
+                self.seek_target(delta_time, effective_width);
+            }
+        } else if self.behavior_mode == BehaviorMode::PerimeterWalk {
+            if self.is_on_ground {
+                self.patrol_perimeter(delta_time, WALK_SPEED, FRICTION, effective_width);
+            }
+        } else if self.behavior_mode == BehaviorMode::ManualControl {
+            // No wandering of its own, the pet just slides to a stop between key presses
+            if self.is_on_ground {
+                self.velocity_x *= (-self.physics.ground_friction * delta_time).exp();
+            }
+        } else if let Some((cursor_x, cursor_y)) = cursor_target.filter(|_| self.is_on_ground) {
+            let dx = cursor_x - pet_center_x;
+            let max_speed = self.physics.max_speed_x;
+
+            let target_vx = match self.behavior_mode {
+                BehaviorMode::FollowCursor if dx.abs() > FOLLOW_STOP_DISTANCE => {
+                    max_speed.copysign(dx)
+                }
+                BehaviorMode::FleeCursor => -max_speed.copysign(dx),
+                _ => 0.0,
+            };
+
+            // A pet dozing next to the cursor is left alone until it has to move
+            if self.current_action != PetAction::Sleeping || target_vx != 0.0 {
+                self.velocity_x += (target_vx - self.velocity_x) * (FRICTION * 1.5 * delta_time).min(1.0);
+                self.velocity_x = self.velocity_x.clamp(-max_speed, max_speed);
+                if target_vx != 0.0 {
+                    self.facing_direction = target_vx > 0.0;
+                }
+
+                if self.behavior_mode == BehaviorMode::FollowCursor
+                    && cursor_y < self.y - FOLLOW_JUMP_HEIGHT
+                {
+                    self.jump();
+                }
+
+                // Backed into a wall with nowhere left to run, jump over the cursor
+                let right_boundary = effective_width - self.pet_width;
+                let cornered = (target_vx < 0.0 && self.x <= 0.0)
+                    || (target_vx > 0.0 && self.x >= right_boundary);
+                if self.behavior_mode == BehaviorMode::FleeCursor && cornered {
+                    self.jump();
+                }
+
+                // Cursor behaviours override whatever the pet was doing on its own
+                self.current_action = PetAction::Idling;
+                self.idle_timer = 0.0;
+            }
+        } else if self.is_on_ground {
+            match self.current_action {
+                PetAction::Idling => {
+                    // Apply friction to bleed off any residual velocity. Exponential
+                    // decay so the pet slides the same distance at any frame rate.
+                    self.velocity_x *= (-self.physics.ground_friction * delta_time).exp();
+
+                    self.idle_timer += delta_time;
+                    // Catch its breath after a landing instead of bouncing straight off again
+                    let settled = self.ground_since.is_none_or(|since| {
+                        now.duration_since(since).as_secs_f32() >= self.behavior.min_ground_dwell
+                    });
+
+                    let mut behavior = self.behavior;
+                    // A worn out pet would rather stay on the ground
+                    behavior.jump_weight *= self.stamina / MAX_STAMINA;
+                    if bursting {
+                        behavior.jump_weight *= self.behavior.burst_intensity;
+                    }
+                    if behavior.reduced_motion {
+                        behavior.walk_weight += behavior.run_weight;
+                        behavior.run_weight = 0.0;
+                        behavior.jump_weight = 0.0;
+                    }
+                    let total_weight =
+                        behavior.walk_weight + behavior.run_weight + behavior.jump_weight;
+                    let mean_idle_period = if bursting { BURST_MEAN_IDLE_PERIOD } else { MEAN_IDLE_PERIOD };
+                    let jump_rate = if total_weight > 0.0 {
+                        behavior.jump_weight / total_weight / mean_idle_period
+                    } else {
+                        0.0
+                    };
+
+                    if settled
+                        && home.is_none()
+                        && jump_rate > 0.0
+                        && self.rng.gen::<f32>() < chance_this_frame(jump_rate, delta_time)
+                    {
+                        // Jump
+                        self.idle_timer = 0.0;
+                        self.jump();
+                        let max_speed = self.physics.max_speed_x;
+                        let speed = self.rng.gen_range(max_speed * 0.3..=max_speed);
+                        self.velocity_x = if self.facing_direction { speed } else { -speed };
+                        self.current_action = PetAction::Idling; // reset after landing
+                    } else if settled && self.idle_timer >= self.idle_duration {
+                        // Decide next action
+                        self.idle_timer = 0.0;
+
+                        let mut sleep_chance: f32 = match self.emotion_state() {
+                            EmotionState::Lonely => 0.15,
+                            EmotionState::Neutral => 0.10,
+                            EmotionState::Happy => 0.07,
+                            EmotionState::Excited => 0.05,
+                        };
+                    
+                        if self.needs.energy < 25.0 {
+                            sleep_chance += 0.15;
+                        } else if self.needs.energy < 50.0 {
+                            sleep_chance += 0.08;
+                        }
+
+                        if self.stamina < LOW_STAMINA {
+                            sleep_chance += 0.10;
+                        }
+
+                        sleep_chance = sleep_chance.min(0.30);
+                        if bursting {
+                            sleep_chance = 0.0;
+                        }
+
+                        let roll: f32 = self.rng.gen();
+
+                        // Jumps were rolled above, this picks between the rest
+                        let move_weight = behavior.walk_weight + behavior.run_weight;
+                        let pick = self.rng.gen::<f32>() * move_weight;
+
+                        let right_boundary = (effective_width - self.pet_width).max(0.0);
+                        let home_x = home.map(|(home_x, _)| (home_x - self.pet_width / 2.0).clamp(0.0, right_boundary));
+
+                        if roll < sleep_chance{
+                            self.fall_asleep();
+                        } 
+                        else if let Some(home_x) = home_x {
+                            // A pet with a home only ever wanders back to it
+                            if (home_x - self.x).abs() > HOME_RADIUS {
+                                self.walk_to(home_x, WALK_SPEED);
+                            }
+                        } else if !behavior.reduced_motion && roll < sleep_chance + CEILING_CLIMB_CHANCE {
+                            self.climb_to_ceiling();
+                        } else if move_weight <= 0.0 {
+                            // Only jumping (or nothing) enabled, just keep idling
+                        } else if pick < behavior.walk_weight {
+                            // Walk to a random spot on the floor
+                            let target_x = self.rng.gen_range(0.0..=right_boundary);
+                            self.walk_to(target_x, WALK_SPEED);
+                        } else {
+                            // Run
+                            self.current_action = PetAction::Running;
+                            self.action_timer = self.rng.gen_range(0.8..2.5);
+                            self.facing_direction = self.rng.gen_bool(0.5);
+                        }
+                        // Next idle will last 1–4 seconds, much less while bursting
+                        self.idle_duration = if bursting {
+                            self.rng.gen_range(0.2..0.8)
+                        } else {
+                            self.rng.gen_range(1.0..4.0)
+                        };
+                    }
+                }
+
+                PetAction::Walking => {
+                    let target_x = self.walk_target_x.unwrap_or(self.x);
+                    let dx = target_x - self.x;
+                    if dx.abs() > WALK_ARRIVE_DISTANCE {
+                        self.facing_direction = dx > 0.0;
+                    }
+
+                    let target_vx = if self.facing_direction { WALK_SPEED } else { -WALK_SPEED };
+                    // Smoothly accelerate toward walk speed
+                    self.velocity_x += (target_vx - self.velocity_x) * (FRICTION * delta_time).min(1.0);
+
+                    self.action_timer -= delta_time;
+                    if dx.abs() <= WALK_ARRIVE_DISTANCE || self.action_timer <= 0.0 {
+                        self.current_action = PetAction::Idling;
+                        self.walk_target_x = None;
+                        self.idle_timer = 0.0;
+                        self.choose_idle_animation();
+                    }
+                }
+
+                PetAction::Running => {
+                    let run_speed = self.physics.max_speed_x;
+                    let target_vx = if self.facing_direction { run_speed } else { -run_speed };
+                    // Faster acceleration for running
+                    self.velocity_x += (target_vx - self.velocity_x) * (FRICTION * 1.5 * delta_time).min(1.0);
+
+                    self.action_timer -= delta_time;
+                    if self.action_timer <= 0.0 {
+                        self.current_action = PetAction::Idling;
+                        self.idle_timer = 0.0;
+                        self.choose_idle_animation();
+                    }
+                }
+
+                PetAction::Sleeping => {
+
+                    const ENERGY_RECOVERY_PER_SECOND: f32 = 0.5;
+
+                    self.needs.energy = 
+                    (self.needs.energy + ENERGY_RECOVERY_PER_SECOND * delta_time).min(100.0);
+
+                    self.velocity_x = 0.0;
+
+                    self.action_timer -= delta_time;
+
+                    self.animation_state = if self.facing_direction {
+                        AnimationState::SleepingRight
+                    } else {
+                        AnimationState::SleepingLeft
+                    };
+
+                    if self.action_timer <= 0.0 {
+                        self.current_action = PetAction::Idling;
+                        self.idle_timer = 0.0;
+                        self.idle_since = None;
+                        self.idle_duration = self.rng.gen_range(1.0..4.0);
+                    }
+                }
+            }
+        }
+
+        // --- Occasional double jump on the way down ---
+        if self.behavior_mode == BehaviorMode::Wander
+            && self.behavior.jump_weight > 0.0
+            && !self.is_on_ground
+            && self.velocity_y > 0.0
+            && self.jumps_remaining > 0
+            && self.rng.gen::<f32>()
+                < chance_this_frame(DOUBLE_JUMP_CHANCE_PER_SECOND * (self.stamina / MAX_STAMINA), delta_time)
+        {
+            self.jump();
+        }
+
+        // --- Wind ---
+        if self.physics.gusty {
+            if self.rng.gen::<f32>() < chance_this_frame(GUST_CHANGE_CHANCE_PER_SECOND, delta_time) {
+                self.gust_target = self.rng.gen_range(0.0..=MAX_GUST_FACTOR);
+            }
+            self.gust += (self.gust_target - self.gust) * delta_time.min(1.0);
+        } else {
+            self.gust = 1.0;
+        }
+
+        let wind = self.physics.wind * self.gust;
+        // Wind blows across the screen whichever way the floor faces
+        let (wind_x, wind_y) = self.physics.gravity_direction.vector_to_local(wind, 0.0);
+        if !self.is_on_ground {
+            self.velocity_x += wind_x * delta_time;
+            self.velocity_y += wind_y * delta_time;
+        } else if self.current_action != PetAction::Sleeping {
+            self.velocity_x += wind_x * GROUND_WIND_FACTOR * delta_time;
+        }
+
+        // --- Attractor, on top of the normal gravity ---
+        if let Some(attractor) = attractor.filter(|_| self.current_action != PetAction::Sleeping) {
+            let dx = attractor.x - (self.x + self.pet_width / 2.0);
+            let dy = attractor.y - (self.y + self.pet_height / 2.0);
+            let distance = dx.hypot(dy).max(ATTRACTOR_MIN_DISTANCE);
+            let pull = (attractor.strength / distance).min(MAX_ATTRACTOR_ACCEL);
+            let (pull_x, pull_y) = (dx / distance * pull, dy / distance * pull);
+
+            // Pulled up harder than gravity holds it down, the pet lifts off,
+            // unless reduced motion keeps it on the floor
+            if self.is_on_ground && -pull_y > self.physics.gravity && !self.behavior.reduced_motion {
+                self.is_on_ground = false;
+            }
+            self.velocity_x += pull_x * delta_time;
+            if !self.is_on_ground {
+                self.velocity_y += pull_y * delta_time;
+            }
+        }
+
+        // Whatever the behaviour, wind or attractor asked for, keep to a gentle walk
+        if self.behavior.reduced_motion && self.is_on_ground {
+            self.velocity_x = self.velocity_x.clamp(-REDUCED_MOTION_SPEED, REDUCED_MOTION_SPEED);
+        }
+
+        // --- Doze off after sitting still for a while ---
+        if self.behavior_mode == BehaviorMode::ManualControl {
+            self.idle_since = None;
+        } else if delta_time == 0.0 {
+            // Frozen by a zero time scale, don't let the wall clock doze it off
+        } else if self.is_on_ground && self.velocity_x.abs() < IDLE_SPEED_THRESHOLD {
+            let idle_since = *self.idle_since.get_or_insert(now);
+            if self.current_action != PetAction::Sleeping
+                && now.duration_since(idle_since).as_secs_f32() >= self.behavior.sleep_after_idle
+            {
+                self.fall_asleep();
+            }
+        } else {
+            self.idle_since = None;
+        }
+
+        self.update_opacity(delta_time, now);
+
+        // --- Gravity, position and boundaries ---
+        let was_on_ground = self.is_on_ground;
+        let mut remaining = delta_time;
+        while remaining > 0.0 {
+            let step = remaining.min(PHYSICS_SUBSTEP);
+            if let Some(impact_speed) = self.integrate(step, effective_width, effective_height) {
+                let cooled_down = self.last_landing_event.is_none_or(|last| {
+                    now.duration_since(last).as_secs_f32() >= self.physics.landing_event_cooldown
+                });
+                if cooled_down && impact_speed >= self.physics.landing_event_min_speed {
+                    self.events.push(PhysicsEvent::Landed(impact_speed));
+                    self.last_landing_event = Some(now);
+                }
+            }
+            remaining -= step;
+        }
+
+        if started_on_ground && !self.is_on_ground && self.velocity_y < 0.0 {
+            self.events.push(PhysicsEvent::Jumped(-self.velocity_y));
+        }
+
+        if !was_on_ground && self.is_on_ground {
+            self.landing_until = Some(now + std::time::Duration::from_secs_f32(LANDING_DURATION));
+            self.ground_since = Some(now);
+        } else if !self.is_on_ground {
+            self.ground_since = None;
+        }
+
+        let is_landing = self.landing_until.is_some_and(|until| now < until);
+        if !is_landing {
+            self.landing_until = None;
+        }
+
+        let is_reacting = self.reaction_until.is_some_and(|until| now < until);
+        if !is_reacting {
+            self.reaction_until = None;
+        }
+
+        let is_startled = self.startled_until.is_some_and(|until| now < until);
+        if !is_startled {
+            self.startled_until = None;
+        }
+
+        // --- Animation state ---
+        let was_running = matches!(
+            self.animation_state,
+            AnimationState::RunningRight | AnimationState::RunningLeft
+        );
+        let run_threshold = if was_running { STOP_THRESHOLD } else { MOVEMENT_THRESHOLD };
+
+        if self.current_action == PetAction::Sleeping {
+            self.animation_state = if self.facing_direction {
+                AnimationState::SleepingRight
+            } else {
+                AnimationState::SleepingLeft
+            };
+            
+        } else if is_startled {
+            self.animation_state = AnimationState::StartledRight.facing(self.facing_direction);
+        } else if is_reacting {
+            self.animation_state = if self.facing_direction { AnimationState::HappyRight } else { AnimationState::HappyLeft };
+        } else if self.is_on_ceiling {
+            self.animation_state = if self.facing_direction { AnimationState::CeilingRight } else { AnimationState::CeilingLeft };
+        } else if !self.is_on_ground {
+            self.animation_state = if self.velocity_y < 0.0 {
+                if self.facing_direction { AnimationState::JumpingRight } else { AnimationState::JumpingLeft }
+            } else {
+                if self.facing_direction { AnimationState::FallingRight } else { AnimationState::FallingLeft }
+            };
+        } else if is_landing {
+            self.animation_state = if self.facing_direction { AnimationState::LandingRight } else { AnimationState::LandingLeft };
+        } else if self.velocity_x.abs() > self.physics.max_speed_x * 0.6 {
+            self.animation_state = if self.velocity_x > 0.0 { AnimationState::RunningRight } else { AnimationState::RunningLeft };
+        } else if self.velocity_x.abs() > run_threshold {
+            self.animation_state = if self.velocity_x > 0.0 { AnimationState::RunningRight } else { AnimationState::RunningLeft };
+        } else {
+            // While the pet is waiting, occasionally use one of the extra idle variants.
+            // The frontend will fall back to normal idle if the current pet does not define it.
+            let facing_overridden = self.facing_override.is_some();
+            if let Some(right) = self.facing_override.take() {
+                self.facing_direction = right;
+                self.animation_state = self.animation_state.facing(right);
+            }
+
+            let currently_idle = matches!(
+                self.animation_state,
+                AnimationState::IdleRight
+                    | AnimationState::IdleLeft
+                    | AnimationState::IdleAlt1Right
+                    | AnimationState::IdleAlt1Left
+                    | AnimationState::IdleAlt2Right
+                    | AnimationState::IdleAlt2Left
+                    | AnimationState::IdleBreathingRight
+                    | AnimationState::IdleBreathingLeft
+            );
+
+            if !currently_idle {
+                // An explicit set_facing wins over the wall
+                if self.behavior.face_away_from_walls && !facing_overridden {
+                    self.face_away_from_wall(effective_width);
+                }
+                self.choose_idle_animation();
+                self.breathing_timer = 0.0;
+            }
+
+            // Only the plain idle breathes, the alternate variants play as they are
+            let plain_idle = matches!(
+                self.animation_state,
+                AnimationState::IdleRight
+                    | AnimationState::IdleLeft
+                    | AnimationState::IdleBreathingRight
+                    | AnimationState::IdleBreathingLeft
+            );
+
+            if self.behavior.idle_breathing && plain_idle {
+                self.breathing_timer += delta_time;
+                if self.breathing_timer >= BREATHING_INTERVAL {
+                    self.breathing_timer = 0.0;
+                    let breathing = matches!(
+                        self.animation_state,
+                        AnimationState::IdleBreathingRight | AnimationState::IdleBreathingLeft
+                    );
+                    let next = if breathing { AnimationState::IdleRight } else { AnimationState::IdleBreathingRight };
+                    self.animation_state = next.facing(self.facing_direction);
+                }
+            } else if self.animation_state.facing(true) == AnimationState::IdleBreathingRight {
+                // Breathing was just switched off mid-breath
+                self.animation_state = AnimationState::IdleRight.facing(self.facing_direction);
+            }
+        }
+
+        // A scripted animation wins over whatever the physics picked
+        match self.forced_animation {
+            Some((forced, until)) if now < until => self.animation_state = forced,
+            Some(_) => self.forced_animation = None,
+            None => {}
+        }
+    }
+}
+
+// Random events that happen at some rate per second are rolled every frame.
+// Rolling rate * dt is only close for short frames: at 10 fps a 2/s rate would
+// give 20% a frame where the real chance is 18%, so the pet would do it more
+// often the slower the tick. 1 - e^(-rate * dt) is the exact chance that it
+// happens at least once during the frame, so the rate holds at any frame rate.
+// Idle jumps are rolled this way too, the other idle decisions are made once
+// per idle period on a timer and need no conversion.
+fn chance_this_frame(per_second: f32, delta_time: f32) -> f32 {
+    1.0 - (-per_second * delta_time).exp()
+}
+
+// Pets bump into each other instead of walking through one another. Only the
+// axis along the floor is resolved, so they can still jump over each other.
+fn resolve_pet_collisions(pets: &mut [PetState]) {
+    for j in 1..pets.len() {
+        let (before, after) = pets.split_at_mut(j);
+        let b = &mut after[0];
+        for a in before.iter_mut() {
+            collide_pets(a, b);
+        }
+    }
+}
+
+fn collide_pets(a: &mut PetState, b: &mut PetState) {
+    // A held pet goes wherever the cursor takes it
+    if a.is_grabbed || b.is_grabbed {
+        return;
+    }
+
+    let (a_width, a_height) = a.screen_size();
+    let (b_width, b_height) = b.screen_size();
+    let overlap_x = (a.x + a_width).min(b.x + b_width) - a.x.max(b.x);
+    let overlap_y = (a.y + a_height).min(b.y + b_height) - a.y.max(b.y);
+    if overlap_x <= 0.0 || overlap_y <= 0.0 {
+        return;
+    }
+
+    let response = (a.physics.collision_response + b.physics.collision_response) / 2.0;
+
+    let (overlap, a_pos, a_size, a_vel, b_pos, b_size, b_vel) =
+        if a.physics.gravity_direction.is_sideways() {
+            (overlap_y, &mut a.y, a_height, &mut a.velocity_y, &mut b.y, b_height, &mut b.velocity_y)
+        } else {
+            (overlap_x, &mut a.x, a_width, &mut a.velocity_x, &mut b.x, b_width, &mut b.velocity_x)
+        };
+
+    // Push each pet half the overlap away from the other
+    let a_first = *a_pos + a_size / 2.0 < *b_pos + b_size / 2.0;
+    let push = if a_first { overlap / 2.0 } else { -overlap / 2.0 };
+    *a_pos -= push;
+    *b_pos += push;
+
+    // Equal masses, so a fully elastic hit swaps the two speeds. Pets already
+    // moving apart are left alone.
+    let closing = if a_first { *a_vel > *b_vel } else { *a_vel < *b_vel };
+    if closing {
+        let (a_speed, b_speed) = (*a_vel, *b_vel);
+        *a_vel += (b_speed - a_speed) * response;
+        *b_vel += (a_speed - b_speed) * response;
+    }
+}
+
+fn save_file_path() -> PathBuf {
+    let mut path = dirs::data_local_dir()
+        .unwrap_or_else(|| std::env::current_dir().unwrap());
+
+    path.push("my-desktop-pet");
+    let _ = fs::create_dir_all(&path);
+
+    path.push("save.json");
+    path
+}
+
+fn state_file_path() -> PathBuf {
+    let mut path = dirs::config_dir()
+        .unwrap_or_else(|| std::env::current_dir().unwrap());
+
+    path.push("my-desktop-pet");
+    let _ = fs::create_dir_all(&path);
+
+    path.push("pet_state.json");
+    path
+}
+
+fn save_state(pets: &[PetState]) {
+    let path = state_file_path();
+
+    match serde_json::to_string_pretty(pets) {
+        Ok(json) => {
+            if let Err(e) = fs::write(&path, json) {
+                println!("Failed to save pet state to {:?}: {:?}", path, e);
+            } else {
+                println!("Saved pet state to {:?}", path);
+            }
+        }
+        Err(e) => println!("Failed to serialize pet state: {:?}", e),
+    }
+}
+
+// Restore the pets from the last session, or start fresh if there is no
+// usable save file. A pet_count from the config wins over however many pets
+// were saved; without one the saved pets are kept as they were.
+fn load_state(window_width: f32, window_height: f32, pet_count: Option<usize>) -> Vec<PetState> {
+    let path = state_file_path();
+    let fresh_count = pet_count.unwrap_or(1);
+
+    let json = match fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(_) => {
+            println!("No saved pet state found, starting fresh");
+            return fresh_pets(window_width, window_height, fresh_count);
+        }
+    };
+
+    match serde_json::from_str::<Vec<PetState>>(&json) {
+        Ok(pets) if !pets.is_empty() => {
+            println!("Restored {} pet(s) from {:?}", pets.len(), path);
+            match pet_count {
+                Some(count) => match_pet_count(pets, count, window_width, window_height),
+                None => pets,
+            }
+        }
+        Ok(_) => {
+            println!("Saved pet state has no pets, starting fresh");
+            fresh_pets(window_width, window_height, fresh_count)
+        }
+        Err(e) => {
+            println!("Saved pet state is corrupt, starting fresh: {:?}", e);
+            fresh_pets(window_width, window_height, fresh_count)
+        }
+    }
+}
+
+// Drop the saved pets past `count`, or top them up with fresh ones that take
+// the primary pet's tuning, the way add_pet does
+fn match_pet_count(mut pets: Vec<PetState>, count: usize, window_width: f32, window_height: f32) -> Vec<PetState> {
+    let count = count.max(1);
+    if pets.len() > count {
+        println!("pet_count is {}, leaving out {} saved pet(s)", count, pets.len() - count);
+        pets.truncate(count);
+    }
+
+    let first_id = pets.iter().map(|pet| pet.id + 1).max().unwrap_or(0);
+    for (id, index) in (first_id..).zip(pets.len()..count) {
+        let mut pet = PetState::new(window_width, window_height);
+        pet.id = id;
+        pet.take_tuning_from(&pets[0]);
+        pet.move_to_spawn();
+        pet.spread_along_floor(index, count);
+        println!("pet_count is {}, adding pet {}", count, pet.id);
+        pets.push(pet);
+    }
+
+    pets
+}
+
+// New pets spread evenly along the floor, so they don't start in a heap
+fn fresh_pets(window_width: f32, window_height: f32, count: usize) -> Vec<PetState> {
+    let count = count.max(1);
+    (0..count)
+        .map(|index| fresh_pet(window_width, window_height, index, count))
+        .collect()
+}
+
+// A new pet standing in the `index`th of `count` evenly spaced spots on the floor
+fn fresh_pet(window_width: f32, window_height: f32, index: usize, count: usize) -> PetState {
+    let mut pet = PetState::new(window_width, window_height);
+    pet.id = index;
+    pet.spread_along_floor(index, count);
+    pet
+}
+
+fn config_file_path() -> PathBuf {
+    let mut path = dirs::config_dir()
+        .unwrap_or_else(|| std::env::current_dir().unwrap());
+
+    path.push("my-desktop-pet");
+    let _ = fs::create_dir_all(&path);
+
+    path.push("pet_config.toml");
+    path
+}
+
+// Read a number from the config file. Wrong types and out of range values
+// are warned about and skipped, so that field keeps its current value.
+fn config_number(
+    table: &toml::Table,
+    key: &str,
+    range: std::ops::RangeInclusive<f32>,
+) -> Option<f32> {
+    let value = table.get(key)?;
+
+    let Some(number) = value.as_float().or_else(|| value.as_integer().map(|n| n as f64)) else {
+        println!("Warning: {} in pet_config.toml should be a number, ignoring it", key);
+        return None;
+    };
+
+    let number = number as f32;
+    if !range.contains(&number) {
+        println!(
+            "Warning: {} in pet_config.toml must be between {} and {}, ignoring {}",
+            key,
+            range.start(),
+            range.end(),
+            number
+        );
+        return None;
+    }
+
+    Some(number)
+}
+
+fn read_config_file() -> Option<toml::Table> {
+    let path = config_file_path();
+
+    let Ok(text) = fs::read_to_string(&path) else {
+        println!("No pet_config.toml found, using the default settings");
+        return None;
+    };
+
+    match toml::from_str::<toml::Table>(&text) {
+        Ok(table) => Some(table),
+        Err(e) => {
+            println!("Warning: could not parse {:?}, using the default settings: {}", path, e);
+            None
+        }
+    }
+}
+
+// Size to create the pets for before the window has been measured
+fn initial_window_size(table: &toml::Table) -> (f32, f32) {
+    let number = |key, range| config_number(table, key, range);
+
+    (
+        number("window_width", MIN_PET_SIZE..=MAX_WINDOW_SIZE).unwrap_or(INITIAL_WINDOW_WIDTH),
+        number("window_height", MIN_PET_SIZE..=MAX_WINDOW_SIZE).unwrap_or(INITIAL_WINDOW_HEIGHT),
+    )
+}
+
+// How many pets the config asks for, None if it doesn't say
+fn initial_pet_count(table: &toml::Table) -> Option<usize> {
+    config_number(table, "pet_count", 1.0..=MAX_STARTUP_PETS as f32).map(|count| count as usize)
+}
+
+// The [animations] table of pet_config.toml, e.g.
+//     [animations.run]
+//     frames = 8
+//     fps = 10
+// A name without -left/-right covers both facings.
+fn config_animation_metadata(table: &toml::Table) -> Option<HashMap<AnimationState, AnimationMetadata>> {
+    let animations = table.get("animations")?.as_table()?;
+
+    let mut metadata = HashMap::new();
+    for (name, entry) in animations {
+        let states: Vec<AnimationState> = [name.clone(), format!("{}-right", name), format!("{}-left", name)]
+            .into_iter()
+            .filter_map(|key| key.parse::<AnimationState>().ok())
+            .collect();
+        if states.is_empty() {
+            println!("Warning: unknown animation {} in pet_config.toml, ignoring it", name);
+            continue;
+        }
+
+        let Some(entry) = entry.as_table() else {
+            println!("Warning: animations.{} in pet_config.toml should be a table, ignoring it", name);
+            continue;
+        };
+        let frames = config_number(entry, "frames", 1.0..=MAX_ANIMATION_FRAMES);
+        let fps = config_number(entry, "fps", 0.1..=MAX_ANIMATION_FPS);
+        let (Some(frames), Some(fps)) = (frames, fps) else {
+            println!("Warning: animations.{} in pet_config.toml needs frames and fps, ignoring it", name);
+            continue;
+        };
+
+        for state in states {
+            metadata.insert(state, AnimationMetadata { frame_count: frames as u32, fps });
+        }
+    }
+
+    Some(metadata)
+}
+
+// Optional pet_config.toml next to the saved state. Only the fields present
+// (and valid) are applied, anything else keeps the current settings.
+fn load_config_file(state: &AppState) {
+    let path = config_file_path();
+    let Some(table) = read_config_file() else {
+        return;
+    };
+
+    if let Some(metadata) = config_animation_metadata(&table) {
+        *state.animation_metadata.lock().unwrap_or_else(PoisonError::into_inner) = metadata;
+    }
+
+    let number = |key, range| config_number(&table, key, range);
+    let gravity = number("gravity", 0.0..=MAX_GRAVITY);
+    let jump_force = number("jump_force", -MAX_JUMP_FORCE..=MAX_JUMP_FORCE);
+    let max_speed_x = number("max_speed_x", 0.0..=MAX_SPEED_X);
+    let restitution_left = number("restitution_left", 0.0..=1.0);
+    let restitution_right = number("restitution_right", 0.0..=1.0);
+    let restitution_floor = number("restitution_floor", 0.0..=1.0);
+    let bounce_threshold = number("bounce_threshold", 0.0..=MAX_BOUNCE_THRESHOLD);
+    let ground_offset = number("ground_offset", 0.0..=MAX_EDGE_OFFSET);
+    let top_offset = number("top_offset", 0.0..=MAX_EDGE_OFFSET);
+    let time_scale = number("time_scale", 0.0..=MAX_TIME_SCALE);
+    let collision_response = number("collision_response", 0.0..=1.0);
+    let landing_event_min_speed = number("landing_event_min_speed", 0.0..=MAX_LANDING_EVENT_SPEED);
+    let landing_event_cooldown = number("landing_event_cooldown", 0.0..=MAX_LANDING_EVENT_COOLDOWN);
+    let jump_probability = number("jump_probability", 0.0..=1.0);
+
+    let behavior_mode = table.get("behavior_mode").and_then(|value| {
+        match value.clone().try_into::<BehaviorMode>() {
+            Ok(mode) => Some(mode),
+            Err(e) => {
+                println!("Warning: invalid behavior_mode in pet_config.toml, ignoring it: {}", e);
+                None
+            }
+        }
+    });
+
+    let flag = |key| {
+        table.get(key).and_then(|value| {
+            let enabled = value.as_bool();
+            if enabled.is_none() {
+                println!("Warning: {} in pet_config.toml must be true or false, ignoring it", key);
+            }
+            enabled
+        })
+    };
+    let spawn_from_top = flag("spawn_from_top");
+    let reduced_motion = flag("reduced_motion");
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        let physics = &mut pet.physics;
+        physics.gravity = gravity.unwrap_or(physics.gravity);
+        physics.jump_force = jump_force.unwrap_or(physics.jump_force);
+        physics.max_speed_x = max_speed_x.unwrap_or(physics.max_speed_x);
+        physics.restitution_left = restitution_left.unwrap_or(physics.restitution_left);
+        physics.restitution_right = restitution_right.unwrap_or(physics.restitution_right);
+        physics.restitution_floor = restitution_floor.unwrap_or(physics.restitution_floor);
+        physics.bounce_threshold = bounce_threshold.unwrap_or(physics.bounce_threshold);
+        physics.ground_offset = ground_offset.unwrap_or(physics.ground_offset);
+        physics.top_offset = top_offset.unwrap_or(physics.top_offset);
+        physics.time_scale = time_scale.unwrap_or(physics.time_scale);
+        physics.spawn_from_top = spawn_from_top.unwrap_or(physics.spawn_from_top);
+        physics.collision_response = collision_response.unwrap_or(physics.collision_response);
+        physics.landing_event_min_speed =
+            landing_event_min_speed.unwrap_or(physics.landing_event_min_speed);
+        physics.landing_event_cooldown = landing_event_cooldown.unwrap_or(physics.landing_event_cooldown);
+
+        if let Some(probability) = jump_probability {
+            pet.behavior.set_jump_probability(probability);
+        }
+        pet.behavior_mode = behavior_mode.unwrap_or(pet.behavior_mode);
+        pet.behavior.reduced_motion = reduced_motion.unwrap_or(pet.behavior.reduced_motion);
+    }
+
+    println!("Loaded settings from {:?}", path);
+}
+
+// Re-apply pet_config.toml whenever it is saved, so settings can be tweaked live
+fn spawn_config_watch(app_handle: tauri::AppHandle) {
+    use notify::{RecursiveMode, Watcher};
+
+    const DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(300);
+
+    std::thread::spawn(move || {
+        let path = config_file_path();
+        let (tx, rx) = std::sync::mpsc::channel::<notify::Result<notify::Event>>();
+
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                println!("Failed to create config watcher, live reload is off: {:?}", e);
+                return;
+            }
+        };
+
+        // Watch the folder rather than the file, editors often save by
+        // replacing the file and that would silently end a file watch
+        let Some(dir) = path.parent() else {
+            return;
+        };
+        if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+            println!("Failed to watch {:?}, live reload is off: {:?}", dir, e);
+            return;
+        }
+
+        let touches_config = |event: &notify::Result<notify::Event>| {
+            event
+                .as_ref()
+                .is_ok_and(|event| event.paths.iter().any(|changed| changed == &path))
+        };
+
+        while let Ok(event) = rx.recv() {
+            if !touches_config(&event) {
+                continue;
+            }
+
+            // A single save can fire several events, wait for them to settle
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+            let Some(state) = app_handle.try_state::<AppState>() else {
+                break;
+            };
+            if state.is_shutting_down() {
+                break;
+            }
+
+            println!("pet_config.toml changed, reloading");
+            load_config_file(&state);
+        }
+    });
+}
+
+// There is always at least one pet. The first one is the "primary" pet that
+// the single-pet commands (get_pet_movement, pet_pet, grab_pet, ...) act on.
+struct AppState {
+    pets: Mutex<Vec<PetState>>,
+    next_pet_id: AtomicUsize,
+    paused: AtomicBool, // freezes the physics, the pets still report where they are
+    span_all_monitors: AtomicBool,
+    window_drag_mode: AtomicBool, // the move-window shortcut is held down
+    tick_hz: AtomicU32,
+    always_on_top: AtomicBool,
+    animation_metadata: Mutex<HashMap<AnimationState, AnimationMetadata>>,
+    pinned_monitor: Mutex<Option<usize>>, // set by move_to_monitor, the window stays on that display
+    shutting_down: AtomicBool, // the window is closing, background loops should wind down
+    window_hidden: AtomicBool, // minimized or hidden, nobody can see the pets so they don't move
+    afk_nap_after: AtomicU32, // seconds without input before the pets nap, 0 never
+    afk_napping: AtomicBool,
+    tick_timings: Mutex<TickTimings>,
+}
+
+impl AppState {
+    fn new(pets: Vec<PetState>) -> Self {
+        let next_pet_id = pets.iter().map(|pet| pet.id + 1).max().unwrap_or(0);
+
+        Self {
+            pets: Mutex::new(pets),
+            next_pet_id: AtomicUsize::new(next_pet_id),
+            paused: AtomicBool::new(false),
+            span_all_monitors: AtomicBool::new(false),
+            window_drag_mode: AtomicBool::new(false),
+            tick_hz: AtomicU32::new(DEFAULT_TICK_HZ),
+            always_on_top: AtomicBool::new(true),
+            animation_metadata: Mutex::new(HashMap::new()),
+            pinned_monitor: Mutex::new(None),
+            shutting_down: AtomicBool::new(false),
+            window_hidden: AtomicBool::new(false),
+            afk_nap_after: AtomicU32::new(DEFAULT_AFK_NAP_AFTER),
+            afk_napping: AtomicBool::new(false),
+            tick_timings: Mutex::new(TickTimings::new()),
+        }
+    }
+
+    // A panic while the lock was held shouldn't brick the pet for good. The
+    // pets are plain data that is still usable, so take it back and carry on.
+    fn lock_pets(&self) -> MutexGuard<'_, Vec<PetState>> {
+        self.pets.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    fn spans_all_monitors(&self) -> bool {
+        self.span_all_monitors.load(Ordering::Relaxed)
+    }
+
+    fn in_window_drag_mode(&self) -> bool {
+        self.window_drag_mode.load(Ordering::Relaxed)
+    }
+
+    fn tick_hz(&self) -> u32 {
+        self.tick_hz.load(Ordering::Relaxed)
+    }
+
+    fn is_always_on_top(&self) -> bool {
+        self.always_on_top.load(Ordering::Relaxed)
+    }
+
+    fn pinned_monitor(&self) -> Option<usize> {
+        *self.pinned_monitor.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn pin_monitor(&self, index: Option<usize>) {
+        *self.pinned_monitor.lock().unwrap_or_else(PoisonError::into_inner) = index;
+    }
+
+    fn tick_timings(&self) -> MutexGuard<'_, TickTimings> {
+        self.tick_timings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_window_hidden(&self) -> bool {
+        self.window_hidden.load(Ordering::Relaxed)
+    }
+
+    // Coming back into view, the pets start timing afresh so their first
+    // update doesn't try to catch up on the time they spent hidden
+    fn set_window_hidden(&self, hidden: bool) {
+        if self.window_hidden.swap(hidden, Ordering::Relaxed) == hidden {
+            return;
+        }
+
+        if !hidden {
+            for pet in self.lock_pets().iter_mut() {
+                pet.last_update = None;
+            }
+        }
+        if hidden {
+            println!("Pet window hidden, physics paused");
+        } else {
+            println!("Pet window shown, physics resumed");
+        }
+    }
+
+    // Put the pets down for a nap while the user is away, and wake them on
+    // the first input after. Pets that dozed off on their own are woken too.
+    fn check_afk_nap(&self) {
+        let Some(idle_seconds) = system_idle_seconds() else {
+            return;
+        };
+        let nap_after = self.afk_nap_after.load(Ordering::Relaxed);
+        let away = nap_after > 0 && idle_seconds >= nap_after as f32;
+        let was_napping = self.afk_napping.swap(away, Ordering::Relaxed);
+
+        let mut pets = self.lock_pets();
+        if away {
+            for pet in pets.iter_mut() {
+                if pet.current_action == PetAction::Sleeping {
+                    pet.action_timer = pet.action_timer.max(AFK_NAP_HOLD);
+                } else if pet.is_on_ground && !pet.is_grabbed {
+                    pet.fall_asleep();
+                }
+            }
+            if !was_napping {
+                println!("No input for {:.0}s, the pets are napping", idle_seconds);
+            }
+        } else if was_napping {
+            for pet in pets.iter_mut() {
+                pet.wake();
+            }
+            println!("User is back, waking the pets");
+        }
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Relaxed)
+    }
+
+    // Tell the background loops to stop, then save the pets. The tick checks
+    // the flag under the pets lock, so nothing moves after they are written.
+    fn shut_down(&self) {
+        self.shutting_down.store(true, Ordering::Relaxed);
+        save_state(&self.lock_pets());
+        println!("Shutting down, pets saved");
+    }
+}
+
+// Ground/air transitions from the last update, for the frontend's sound effects.
+// Jumped means the pet left the ground moving up, whatever lifted it: its own
+// jumps, the attractor, or a hop or push from a command since the last update.
+// Being grabbed, teleported or climbing onto the ceiling isn't a jump.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PhysicsEvent {
+    Jumped(f32), // take-off speed
+    Landed(f32), // impact speed
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PhysicsEventPayload {
+    id: usize,
+    speed: f32,
+}
+
+// Real time between the latest physics ticks, to spot stutter
+#[derive(Debug)]
+struct TickTimings {
+    last_tick: Option<Instant>,
+    deltas: VecDeque<f32>, // seconds, newest at the back
+}
+
+impl TickTimings {
+    fn new() -> Self {
+        Self {
+            last_tick: None,
+            deltas: VecDeque::with_capacity(PERF_WINDOW),
+        }
+    }
+
+    fn record(&mut self, now: Instant) {
+        if let Some(last) = self.last_tick {
+            if self.deltas.len() == PERF_WINDOW {
+                self.deltas.pop_front();
+            }
+            self.deltas.push_back(now.duration_since(last).as_secs_f32());
+        }
+        self.last_tick = Some(now);
+    }
+
+    fn stats(&self, target_hz: u32) -> PerfStats {
+        let total: f32 = self.deltas.iter().sum();
+        let average = if self.deltas.is_empty() { 0.0 } else { total / self.deltas.len() as f32 };
+        let max = self.deltas.iter().copied().fold(0.0, f32::max);
+
+        PerfStats {
+            average_delta_ms: average * 1000.0,
+            max_delta_ms: max * 1000.0,
+            updates_per_second: if average > 0.0 { 1.0 / average } else { 0.0 },
+            target_hz,
+            samples: self.deltas.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PerfStats {
+    average_delta_ms: f32,
+    max_delta_ms: f32,
+    updates_per_second: f32, // what the tick actually manages, compare with target_hz
+    target_hz: u32,
+    samples: usize,
+}
+
+// The active behaviour mode and every behaviour tunable, for the settings panel
+#[derive(Debug, Clone, Serialize)]
+struct BehaviorSnapshot {
+    mode: BehaviorMode,
+    jump_probability: f32,
+    #[serde(flatten)]
+    config: BehaviorConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PetSnapshot {
+    id: usize,
+    x: f32,
+    y: f32,
+    velocity_x: f32,
+    velocity_y: f32,
+    is_on_ground: bool,
+    facing_direction: bool,
+    width: f32,
+    height: f32,
+    animation: AnimationState,
+    stamina: f32,
+    gravity: GravityDirection, // the frontend rotates the sprite to stand on that edge
+    skin: String,
+    animation_speed: f32, // 1.0 plays the animation at its normal rate
+    scale_x: f32,         // squash-and-stretch hints, between 0.8 and 1.2
+    scale_y: f32,
+    frame_count: u64,
+    sim_time: f32, // seconds of (scaled) simulation since startup
+    opacity: f32,
+}
+
+
+// Garbage from the frontend (e.g. NaN before layout) must not reach the
+// physics: keep the pet's last known window size and ignore the cursor
+fn frame_inputs(
+    pet: &PetState,
+    window_width: f32,
+    window_height: f32,
+    cursor_x: Option<f32>,
+    cursor_y: Option<f32>,
+) -> (f32, f32, Option<(f32, f32)>) {
+    let window_width = if window_width.is_finite() { window_width } else { pet.window_width };
+    let window_height = if window_height.is_finite() { window_height } else { pet.window_height };
+    let cursor = cursor_x
+        .zip(cursor_y)
+        .filter(|(x, y)| x.is_finite() && y.is_finite());
+
+    (window_width, window_height, cursor)
+}
+
+// The polling commands advance the pets like the physics tick does, so they
+// send on the jump and landing events from that update the same way
+#[tauri::command]
+fn get_pet_snapshot(
+    window: tauri::WebviewWindow,
+    state: State<AppState>,
+    window_width: f32,
+    window_height: f32,
+    cursor_x: Option<f32>,
+    cursor_y: Option<f32>,
+) -> PetSnapshot {
+    let (snapshot, events) = {
+        let mut pets = state.lock_pets();
+        let pet = &mut pets[0];
+        let (window_width, window_height, cursor) =
+            frame_inputs(pet, window_width, window_height, cursor_x, cursor_y);
+
+        // Update pet with the current window dimensions
+        if !state.is_paused() {
+            pet.update(window_width, window_height, cursor, Instant::now());
+        }
+
+        (pet.snapshot(), take_physics_events(&mut pets))
+    };
+
+    emit_physics_events(&window, events);
+    snapshot
+}
+
+#[tauri::command]
+fn get_pet_movement(
+    window: tauri::WebviewWindow,
+    state: State<AppState>,
+    window_width: f32,
+    window_height: f32,
+    cursor_x: Option<f32>,
+    cursor_y: Option<f32>,
+) -> (f32, f32, AnimationState) {
+    let snapshot = get_pet_snapshot(window, state, window_width, window_height, cursor_x, cursor_y);
+    (snapshot.x, snapshot.y, snapshot.animation)
+}
+
+// How smoothly the physics tick is running. The averages cover the last
+// PERF_WINDOW ticks, including ones slowed down while the pets rest.
+#[tauri::command]
+fn get_perf_stats(state: State<AppState>) -> PerfStats {
+    let target_hz = state.tick_hz();
+    let timings = state.tick_timings();
+    timings.stats(target_hz)
+}
+
+// Every pet in full as JSON, for save slots or sharing a pet. Same format
+// as pet_state.json, so a blob can be dropped in there too.
+#[tauri::command]
+fn export_state(state: State<AppState>) -> Result<String, String> {
+    let pets = state.lock_pets();
+    serde_json::to_string_pretty(&*pets).map_err(|e| format!("Failed to serialize pet state: {:?}", e))
+}
+
+// Replace every pet with the ones from an export_state blob. Their timing
+// starts afresh and they are pulled inside the current window.
+#[tauri::command]
+fn import_state(state: State<AppState>, blob: String) -> Result<PetSnapshot, String> {
+    let mut imported: Vec<PetState> =
+        serde_json::from_str(&blob).map_err(|e| format!("Invalid pet state: {}", e))?;
+    if imported.is_empty() {
+        return Err("Pet state has no pets".to_string());
+    }
+
+    let mut pets = state.lock_pets();
+    let (window_width, window_height) = (pets[0].window_width, pets[0].window_height);
+    let mut ids = HashSet::new();
+    for pet in imported.iter_mut() {
+        if !ids.insert(pet.id) {
+            return Err(format!("Pet state has more than one pet with id {}", pet.id));
+        }
+
+        let valid_size = [pet.pet_width, pet.pet_height]
+            .iter()
+            .all(|size| size.is_finite() && *size >= MIN_PET_SIZE);
+        if !valid_size || !pet.x.is_finite() || !pet.y.is_finite() {
+            return Err(format!("Pet {} has an invalid size or position", pet.id));
+        }
+        // Same limits as the setters, so nothing gets in that they would refuse
+        pet.physics
+            .validate()
+            .and_then(|()| pet.behavior.validate())
+            .map_err(|e| format!("Pet {}: {}", pet.id, e))?;
+
+        pet.fit_to_window(window_width, window_height);
+        pet.shrink_to_fit();
+        if pet.window_height - pet.physics.ground_offset - pet.physics.top_offset < pet.screen_size().1 {
+            return Err(format!("Pet {}: edge offsets leave no room for the pet", pet.id));
+        }
+    }
+
+    let next_pet_id = imported.iter().map(|pet| pet.id + 1).max().unwrap_or(0);
+    state.next_pet_id.fetch_max(next_pet_id, Ordering::Relaxed);
+    *pets = imported;
+
+    println!("Imported {} pet(s)", pets.len());
+    Ok(pets[0].snapshot())
+}
+
+// Every field of every pet as text, for a dev overlay. Only reads the state.
+#[tauri::command]
+fn debug_dump(state: State<AppState>) -> String {
+    let pets = state.lock_pets();
+
+    let mut dump = format!(
+        "paused: {}, tick rate: {} Hz, pets: {}\n",
+        state.is_paused(),
+        state.tick_hz(),
+        pets.len()
+    );
+    for pet in pets.iter() {
+        dump.push_str(&format!("\n{:#?}\n", pet));
+    }
+    dump
+}
+
+// Only the animations pet_config.toml sets, the rest use the frontend's timing
+#[tauri::command]
+fn get_animation_metadata(state: State<AppState>) -> HashMap<AnimationState, AnimationMetadata> {
+    state.animation_metadata.lock().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+// Serialized through serde, so the frontend gets the same names as in pet-update
+#[tauri::command]
+fn list_animation_states() -> Vec<AnimationState> {
+    AnimationState::ALL.to_vec()
+}
+
+#[tauri::command]
+fn set_confinement(
+    state: State<AppState>,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) -> Result<(), String> {
+    if [x, y, width, height].iter().any(|value| !value.is_finite()) {
+        return Err("Confinement rectangle must be finite numbers".to_string());
+    }
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter() {
+        if x < 0.0 || y < 0.0 || x + width > pet.window_width || y + height > pet.window_height {
+            return Err(format!(
+                "Confinement {}x{} at ({}, {}) does not fit inside the {}x{} window",
+                width, height, x, y, pet.window_width, pet.window_height
+            ));
+        }
+
+        let (pet_width, pet_height) = pet.screen_size();
+        if width < pet_width || height < pet_height {
+            return Err(format!("Confinement {}x{} is smaller than the pet", width, height));
+        }
+    }
+
+    for pet in pets.iter_mut() {
+        pet.confinement = Some(Confinement { x, y, width, height });
+    }
+
+    println!("Pets confined to {}x{} at ({}, {})", width, height, x, y);
+    Ok(())
+}
+
+#[tauri::command]
+fn clear_confinement(state: State<AppState>) {
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.confinement = None;
+    }
+
+    println!("Pets can use the whole window again");
+}
+
+#[tauri::command]
+fn apply_impulse(state: State<AppState>, dx: f32, dy: f32) -> Result<PetSnapshot, String> {
+    if !dx.is_finite() || !dy.is_finite() {
+        return Err(format!("Impulse must be finite, got ({}, {})", dx, dy));
+    }
+
+    let mut pets = state.lock_pets();
+    let pet = &mut pets[0];
+    pet.apply_impulse(dx, dy);
+
+    Ok(pet.snapshot())
+}
+
+// Idle pets turn around straight away, moving ones once they next stop
+#[tauri::command]
+fn set_facing(state: State<AppState>, right: bool) -> PetSnapshot {
+    let mut pets = state.lock_pets();
+    let pet = &mut pets[0];
+
+    if pet.is_resting() {
+        pet.facing_direction = right;
+        pet.animation_state = pet.animation_state.facing(right);
+        pet.facing_override = None;
+    } else {
+        pet.facing_override = Some(right);
+    }
+
+    pet.snapshot()
+}
+
+#[tauri::command]
+fn predict_trajectory(state: State<AppState>, steps: u32) -> Vec<(f32, f32)> {
+    state.lock_pets()[0].predict_trajectory(steps.min(MAX_PREDICTION_STEPS))
+}
+
+// Up to `count` of the primary pet's latest positions, oldest first, for
+// drawing a motion trail behind it
+#[tauri::command]
+fn get_trail(state: State<AppState>, count: usize) -> Vec<(f32, f32)> {
+    state.lock_pets()[0].recent_trail(count.min(TRAIL_CAPACITY))
+}
+
+// The primary pet's on-screen rectangle as (x, y, width, height), read
+// without advancing the physics
+#[tauri::command]
+fn get_pet_bounds(state: State<AppState>) -> (f32, f32, f32, f32) {
+    let pets = state.lock_pets();
+    let pet = &pets[0];
+    let (width, height) = pet.screen_size();
+
+    (pet.x, pet.y, width, height)
+}
+
+// Whether the primary pet's box overlaps the rectangle at all, for triggers
+// like "the pet is by the clock". Doesn't advance the physics.
+#[tauri::command]
+fn is_pet_in_rect(state: State<AppState>, x: f32, y: f32, width: f32, height: f32) -> Result<bool, String> {
+    if [x, y, width, height].iter().any(|value| !value.is_finite()) || width < 0.0 || height < 0.0 {
+        return Err(format!("Invalid rectangle {}x{} at ({}, {})", width, height, x, y));
+    }
+
+    let pets = state.lock_pets();
+    let pet = &pets[0];
+    let (pet_width, pet_height) = pet.screen_size();
+
+    Ok(pet.x < x + width && x < pet.x + pet_width && pet.y < y + height && y < pet.y + pet_height)
+}
+
+// Cheap status check, reads the flag without advancing the physics
+#[tauri::command]
+fn is_pet_airborne(state: State<AppState>) -> bool {
+    let pets = state.lock_pets();
+    !pets[0].is_on_ground
+}
+
+#[tauri::command]
+fn pet_pet(state: State<AppState>) {
+    let mut pets = state.lock_pets();
+    let pet = &mut pets[0];
+
+    let was_already_loved = pet.love_timer > 0.0;
+
+    pet.love_timer = 3.0;
+
+    pet.gain_affection();
+
+    println!("Affection: {}", pet.needs.affection);
+    pet.velocity_x = 0.0;
+    pet.velocity_y = 0.0;
+    pet.current_action = PetAction::Idling;
+
+    if !was_already_loved {
+        pet.choose_idle_animation();
+    }
+}
+
+// Generic hook for making the pet jump in fright, e.g. from a hotkey or a
+// notification. Returns false if it was ignored because of the cooldown.
+#[tauri::command]
+fn startle(state: State<AppState>) -> bool {
+    let mut pets = state.lock_pets();
+    let startled = pets[0].startle(Instant::now());
+
+    if startled {
+        println!("Pet startled");
+    }
+    startled
+}
+
+// A click gets a quick happy hop rather than pet_pet's long cuddle
+#[tauri::command]
+fn pet_clicked(state: State<AppState>) {
+    let mut pets = state.lock_pets();
+    let pet = &mut pets[0];
+
+    pet.wake();
+    pet.gain_affection();
+    pet.love_timer = 0.0; // otherwise the hop would wait for the cuddle to end
+    pet.current_action = PetAction::Idling;
+    pet.velocity_x = 0.0;
+
+    if pet.is_on_ground && !pet.behavior.reduced_motion {
+        pet.velocity_y = pet.physics.jump_force * 0.5;
+        pet.is_on_ground = false;
+    }
+    pet.reaction_until = Some(Instant::now() + std::time::Duration::from_secs_f32(REACTION_DURATION));
+
+    println!("Pet clicked, affection: {}", pet.needs.affection);
+}
+
+// Play one animation for a while no matter what the pet is doing, for
+// scripted scenes. Names are the ones list_animation_states returns.
+#[tauri::command]
+fn force_animation(state: State<AppState>, animation: String, duration_ms: u64) -> Result<(), String> {
+    let forced: AnimationState = animation.parse()?;
+    if duration_ms > MAX_FORCED_ANIMATION_MS {
+        return Err(format!(
+            "Duration must be at most {}ms, got {}",
+            MAX_FORCED_ANIMATION_MS, duration_ms
+        ));
+    }
+
+    let mut pets = state.lock_pets();
+    let pet = &mut pets[0];
+    let until = Instant::now() + std::time::Duration::from_millis(duration_ms);
+    pet.forced_animation = Some((forced, until));
+    pet.animation_state = forced;
+
+    println!("Forcing animation {} for {}ms", animation, duration_ms);
+    Ok(())
+}
+
+#[tauri::command]
+fn move_left(state: State<AppState>) {
+    state.lock_pets()[0].steer(-1.0);
+}
+
+#[tauri::command]
+fn move_right(state: State<AppState>) {
+    state.lock_pets()[0].steer(1.0);
+}
+
+#[tauri::command]
+fn jump(state: State<AppState>) {
+    let mut pets = state.lock_pets();
+    let pet = &mut pets[0];
+
+    pet.wake();
+    pet.current_action = PetAction::Idling;
+    pet.jump();
+}
+
+#[tauri::command]
+fn wake_pet(state: State<AppState>) {
+    let mut pets = state.lock_pets();
+    pets[0].wake();
+}
+
+#[tauri::command]
+fn pause_pet(state: State<AppState>) {
+    state.paused.store(true, Ordering::Relaxed);
+    println!("Pet physics paused");
+}
+
+#[tauri::command]
+fn resume_pet(state: State<AppState>) {
+    let mut pets = state.lock_pets();
+
+    // Start timing from now, otherwise the first frame after resuming would
+    // try to catch up on the whole pause
+    let now = Instant::now();
+    for pet in pets.iter_mut() {
+        pet.last_update = Some(now);
+    }
+
+    state.paused.store(false, Ordering::Relaxed);
+    println!("Pet physics resumed");
+}
+
+// Like reset_pet_position, but the fresh pet can start against any edge of the window
+#[tauri::command]
+fn spawn_at_edge(state: State<AppState>, edge: String) -> Result<PetSnapshot, String> {
+    let mut pets = state.lock_pets();
+    let pet = &mut pets[0];
+    let (window_width, window_height) = (pet.window_width, pet.window_height);
+
+    match edge.as_str() {
+        "floor" => {
+            pet.reset(window_width, window_height);
+        }
+        "ceiling" => {
+            pet.reset(window_width, window_height);
+            pet.climb_to_ceiling();
+            pet.animation_state = if pet.facing_direction {
+                AnimationState::CeilingRight
+            } else {
+                AnimationState::CeilingLeft
+            };
+        }
+        // Against a wall, standing on the floor and facing into the window
+        "left" => {
+            pet.reset(window_width, window_height);
+            pet.x = 0.0;
+            pet.facing_direction = true;
+            pet.animation_state = AnimationState::IdleRight;
+        }
+        "right" => {
+            pet.reset(window_width, window_height);
+            pet.x = (window_width - pet.pet_width).max(0.0);
+            pet.facing_direction = false;
+            pet.animation_state = AnimationState::IdleLeft;
+        }
+        _ => {
+            return Err(format!(
+                "Unknown edge \"{}\", expected floor, ceiling, left or right",
+                edge
+            ));
+        }
+    }
+
+    println!("Pet {} spawned at the {} edge", pet.id, edge);
+    Ok(pet.snapshot())
+}
+
+#[tauri::command]
+fn set_tick_rate(state: State<AppState>, hz: u32) -> Result<(), String> {
+    if !(MIN_TICK_HZ..=MAX_TICK_HZ).contains(&hz) {
+        return Err(format!("Tick rate must be between {} and {}Hz, got {}", MIN_TICK_HZ, MAX_TICK_HZ, hz));
+    }
+
+    state.tick_hz.store(hz, Ordering::Relaxed);
+    println!("Physics tick rate set to {}Hz", hz);
+    Ok(())
+}
+
+#[tauri::command]
+fn teleport_pet(state: State<AppState>, x: f32, y: f32) -> Result<PetSnapshot, String> {
+    if !x.is_finite() || !y.is_finite() {
+        return Err(format!("Teleport target must be finite, got ({}, {})", x, y));
+    }
+
+    let mut pets = state.lock_pets();
+    let pet = &mut pets[0];
+    pet.teleport(x, y);
+
+    Ok(pet.snapshot())
+}
+
+#[tauri::command]
+fn grab_pet(state: State<AppState>, cursor_x: f32, cursor_y: f32) -> Result<(), String> {
+    if !cursor_x.is_finite() || !cursor_y.is_finite() {
+        return Err(format!("Cursor must be a finite position, got ({}, {})", cursor_x, cursor_y));
+    }
+
+    let mut pets = state.lock_pets();
+    pets[0].grab(cursor_x, cursor_y);
+    Ok(())
+}
+
+#[tauri::command]
+fn release_pet(app: tauri::AppHandle, state: State<AppState>) {
+    let flung_off_screen = {
+        let mut pets = state.lock_pets();
+        pets[0].release();
+        pets[0].was_flung_off_screen()
+    };
+
+    if flung_off_screen {
+        dismiss_to_tray(&app, &state);
+    }
+}
+
+// Hide the pet until "Show Pet" is picked from the tray menu
+fn dismiss_to_tray(app: &tauri::AppHandle, state: &AppState) {
+    state.paused.store(true, Ordering::Relaxed);
+
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+        if let Err(e) = window.hide() {
+            println!("Failed to hide the pet window: {:?}", e);
+            state.paused.store(false, Ordering::Relaxed);
+            return;
+        }
+    }
+
+    println!("Pet thrown off screen, sent to the tray");
+}
+
+fn show_from_tray(app: &tauri::AppHandle) {
+    let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) else {
+        return;
+    };
+
+    let (window_width, window_height) =
+        logical_window_size(&window).unwrap_or((INITIAL_WINDOW_WIDTH, INITIAL_WINDOW_HEIGHT));
+    if let Err(e) = reset_pet_position(app.state(), window_width, window_height, None) {
+        println!("Failed to reset the pet: {}", e);
+    }
+    resume_pet(app.state());
+
+    if let Err(e) = window.show() {
+        println!("Failed to show the pet window: {:?}", e);
+    }
+}
+
+#[tauri::command]
+fn get_behavior(state: State<AppState>) -> BehaviorSnapshot {
+    let pets = state.lock_pets();
+    let pet = &pets[0];
+
+    BehaviorSnapshot {
+        mode: pet.behavior_mode,
+        jump_probability: pet.behavior.jump_probability(),
+        config: pet.behavior,
+    }
+}
+
+#[tauri::command]
+fn get_pet_stats(state: State<AppState>) -> (f32, f32, f32, String) {
+    let pets = state.lock_pets();
+    let pet = &pets[0];
+
+
+    let mood = match pet.emotion_state() {
+        EmotionState::Lonely => "Lonely",
+        EmotionState::Neutral => "Neutral",
+        EmotionState::Happy => "Happy",
+        EmotionState::Excited => "Excited",
+    };
+    (
+        pet.needs.affection,
+        pet.needs.hunger,
+        pet.needs.energy,
+        mood.to_string(),
+    )
+}
+
+#[tauri::command]
+fn get_all_pets_movement(
+    window: tauri::WebviewWindow,
+    state: State<AppState>,
+    window_width: f32,
+    window_height: f32,
+    cursor_x: Option<f32>,
+    cursor_y: Option<f32>,
+) -> Vec<(usize, f32, f32, AnimationState)> {
+    let (movement, events) = {
+        let mut pets = state.lock_pets();
+        let (window_width, window_height, cursor) =
+            frame_inputs(&pets[0], window_width, window_height, cursor_x, cursor_y);
+        let paused = state.is_paused();
+        let now = Instant::now();
+
+        if !paused {
+            for pet in pets.iter_mut() {
+                pet.update(window_width, window_height, cursor, now);
+            }
+            resolve_pet_collisions(&mut pets);
+        }
+
+        let movement: Vec<_> = pets
+            .iter()
+            .map(|pet| (pet.id, pet.x, pet.y, pet.animation_state))
+            .collect();
+        (movement, take_physics_events(&mut pets))
+    };
+
+    emit_physics_events(&window, events);
+    movement
+}
+
+// Every pet advanced once and returned in one go, instead of one round trip
+// per pet each frame. The window size and cursor are shared by all of them.
+#[tauri::command]
+fn get_all_snapshots(
+    window: tauri::WebviewWindow,
+    state: State<AppState>,
+    window_width: f32,
+    window_height: f32,
+    cursor_x: Option<f32>,
+    cursor_y: Option<f32>,
+) -> Vec<PetSnapshot> {
+    let (snapshots, events) = {
+        let mut pets = state.lock_pets();
+        let (window_width, window_height, cursor) =
+            frame_inputs(&pets[0], window_width, window_height, cursor_x, cursor_y);
+
+        if !state.is_paused() {
+            let now = Instant::now();
+            for pet in pets.iter_mut() {
+                pet.update(window_width, window_height, cursor, now);
+            }
+            resolve_pet_collisions(&mut pets);
+        }
+
+        let snapshots: Vec<PetSnapshot> = pets.iter().map(PetState::snapshot).collect();
+        (snapshots, take_physics_events(&mut pets))
+    };
+
+    emit_physics_events(&window, events);
+    snapshots
+}
+
+#[tauri::command]
+fn add_pet(
+    state: State<AppState>,
+    window_width: f32,
+    window_height: f32,
+    skin: Option<String>,
+) -> usize {
+    let mut pets = state.lock_pets();
+
+    let mut pet = PetState::new(window_width, window_height);
+    pet.id = state.next_pet_id.fetch_add(1, Ordering::Relaxed);
+    pet.take_tuning_from(&pets[0]);
+    pet.skin = skin.unwrap_or_default();
+    pet.move_to_spawn(); // again, now that it knows where to spawn
+
+    let id = pet.id;
+    println!("Added pet {}", id);
+    pets.push(pet);
+    id
+}
+
+#[tauri::command]
+fn remove_pet(state: State<AppState>, id: usize) -> Result<(), String> {
+    let mut pets = state.lock_pets();
+
+    let Some(index) = pets.iter().position(|pet| pet.id == id) else {
+        return Err(format!("No pet with id {}", id));
+    };
+    if pets.len() == 1 {
+        return Err("Cannot remove the last pet".to_string());
+    }
+
+    pets.remove(index);
+    println!("Removed pet {}", id);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_pet_skin(state: State<AppState>, id: usize, skin: String) -> Result<(), String> {
+    let mut pets = state.lock_pets();
+
+    let Some(pet) = pets.iter_mut().find(|pet| pet.id == id) else {
+        return Err(format!("No pet with id {}", id));
+    };
+
+    println!("Pet {} skin set to {:?}", id, skin);
+    pet.skin = skin;
+    Ok(())
+}
+
+// Re-centres a pet on the floor, keeping its id, size and tuning.
+// With an id only that pet is reset and returned; without one every pet is
+// reset and the primary pet is returned.
+#[tauri::command]
+fn reset_pet_position(
+    state: State<AppState>,
+    window_width: f32,
+    window_height: f32,
+    id: Option<usize>,
+) -> Result<PetSnapshot, String> {
+    let valid = |size: f32| size.is_finite() && size > 0.0;
+    if !valid(window_width) || !valid(window_height) {
+        return Err(format!(
+            "Window size must be positive, got {}x{}",
+            window_width, window_height
+        ));
+    }
+
+    let mut pets = state.lock_pets();
+
+    let reset = |pet: &mut PetState| pet.reset(window_width, window_height);
+
+    let pet = match id {
+        Some(id) => {
+            let Some(pet) = pets.iter_mut().find(|pet| pet.id == id) else {
+                return Err(format!("No pet with id {}", id));
+            };
+            reset(pet);
+            pet
+        }
+        None => {
+            pets.iter_mut().for_each(reset);
+            &pets[0]
+        }
+    };
+
+    Ok(pet.snapshot())
+}
+
+#[tauri::command]
+fn set_physics_config(
+    state: State<AppState>,
+    gravity: f32,
+    jump_force: f32,
+    max_speed_x: f32,
+) -> Result<(), String> {
+    PhysicsConfig::check_motion(gravity, jump_force, max_speed_x)?;
+
+    let gravity = gravity.min(MAX_GRAVITY);
+    let jump_force = jump_force.clamp(-MAX_JUMP_FORCE, MAX_JUMP_FORCE);
+    let max_speed_x = max_speed_x.clamp(0.0, MAX_SPEED_X);
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.gravity = gravity;
+        pet.physics.jump_force = jump_force;
+        pet.physics.max_speed_x = max_speed_x;
+    }
+
+    println!("Physics config updated: {:?}", pets[0].physics);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_restitution(state: State<AppState>, left: f32, right: f32, floor: f32) -> Result<(), String> {
+    PhysicsConfig::check_restitution(left, right, floor)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.restitution_left = left;
+        pet.physics.restitution_right = right;
+        pet.physics.restitution_floor = floor;
+    }
+
+    println!("Restitution set to left {}, right {}, floor {}", left, right, floor);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_gravity_direction(state: State<AppState>, direction: GravityDirection) {
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        if pet.physics.gravity_direction == direction {
+            continue;
+        }
+
+        pet.physics.gravity_direction = direction;
+        // Let go of whatever it was standing on and fall towards the new floor
+        pet.is_on_ground = false;
+        pet.is_on_ceiling = false;
+        pet.current_action = PetAction::Idling;
+        pet.walk_target_x = None;
+    }
+
+    println!("Gravity direction set to {:?}", direction);
+}
+
+#[tauri::command]
+fn set_terminal_velocity(state: State<AppState>, speed: f32) -> Result<(), String> {
+    PhysicsConfig::check_terminal_velocity(speed)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.terminal_velocity = speed;
+    }
+
+    println!("Terminal velocity set to {}", speed);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_spawn_from_top(state: State<AppState>) -> bool {
+    state.lock_pets()[0].physics.spawn_from_top
+}
+
+#[tauri::command]
+fn set_spawn_from_top(state: State<AppState>, enabled: bool) {
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.spawn_from_top = enabled;
+    }
+
+    println!("Spawn from top set to: {}", enabled);
+}
+
+#[tauri::command]
+fn set_collision_response(state: State<AppState>, factor: f32) -> Result<(), String> {
+    PhysicsConfig::check_collision_response(factor)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.collision_response = factor;
+    }
+
+    println!("Collision response set to {}", factor);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_landing_event_filter(
+    state: State<AppState>,
+    min_speed: f32,
+    cooldown_ms: u32,
+) -> Result<(), String> {
+    let cooldown = cooldown_ms as f32 / 1000.0;
+    PhysicsConfig::check_landing_events(min_speed, cooldown)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.landing_event_min_speed = min_speed;
+        pet.physics.landing_event_cooldown = cooldown;
+    }
+
+    println!("Landing events need {} px/s and {}ms between them", min_speed, cooldown_ms);
+    Ok(())
+}
+
+// 0 turns napping off
+#[tauri::command]
+fn set_afk_nap(state: State<AppState>, seconds: u32) -> Result<(), String> {
+    if seconds > MAX_AFK_NAP_AFTER {
+        return Err(format!("Nap delay must be at most {}s, got {}", MAX_AFK_NAP_AFTER, seconds));
+    }
+
+    state.afk_nap_after.store(seconds, Ordering::Relaxed);
+    println!("Pets nap after {}s without input", seconds);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_bounce_threshold(state: State<AppState>, speed: f32) -> Result<(), String> {
+    PhysicsConfig::check_bounce_threshold(speed)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.bounce_threshold = speed;
+    }
+
+    println!("Bounce threshold set to {} px/s", speed);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_wind(state: State<AppState>, force: f32, gusty: bool) -> Result<(), String> {
+    PhysicsConfig::check_wind(force)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.wind = force;
+        pet.physics.gusty = gusty;
+    }
+
+    println!("Wind set to {} (gusty: {})", force, gusty);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_time_scale(state: State<AppState>, scale: f32) -> Result<(), String> {
+    PhysicsConfig::check_time_scale(scale)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.time_scale = scale;
+    }
+
+    println!("Time scale set to {}", scale);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_ground_offset(state: State<AppState>, bottom: f32, top: f32) -> Result<(), String> {
+    PhysicsConfig::check_edge_offsets(bottom, top)?;
+
+    let mut pets = state.lock_pets();
+    if pets.iter().any(|pet| pet.window_height - bottom - top < pet.screen_size().1) {
+        return Err("Edge offsets leave no room for the pet".to_string());
+    }
+
+    for pet in pets.iter_mut() {
+        pet.physics.ground_offset = bottom;
+        pet.physics.top_offset = top;
+    }
+
+    println!("Edge offsets set to bottom {}px, top {}px", bottom, top);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_air_drag(state: State<AppState>, drag: f32) -> Result<(), String> {
+    PhysicsConfig::check_air_drag(drag)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.air_drag = drag;
+    }
+
+    println!("Air drag set to {}", drag);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_ground_friction(state: State<AppState>, friction: f32) -> Result<(), String> {
+    PhysicsConfig::check_ground_friction(friction)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.ground_friction = friction;
+    }
+
+    println!("Ground friction set to {}", friction);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_pet_size(state: State<AppState>, width: f32, height: f32) -> Result<(), String> {
+    if !width.is_finite() || !height.is_finite() || width < MIN_PET_SIZE || height < MIN_PET_SIZE {
+        return Err(format!("Pet size must be at least {}px, got {}x{}", MIN_PET_SIZE, width, height));
+    }
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter() {
+        // Sideways gravity turns the pet on its side, like set_confinement checks
+        let (screen_width, screen_height) = if pet.physics.gravity_direction.is_sideways() {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        let (_, _, area_width, area_height) = pet.play_area(pet.window_width, pet.window_height);
+        if screen_width > area_width || screen_height > area_height {
+            return Err(format!(
+                "Pet size {}x{} does not fit inside the {}x{} play area",
+                width, height, area_width, area_height
+            ));
+        }
+    }
+
+    for pet in pets.iter_mut() {
+        pet.pet_width = width;
+        pet.pet_height = height;
+        // Pull it back inside so the bigger pet doesn't poke through the floor or walls
+        let (origin_x, origin_y, area_width, area_height) = pet.play_area(pet.window_width, pet.window_height);
+        let (screen_width, screen_height) = pet.screen_size();
+        pet.x = pet.x.min(origin_x + area_width - screen_width).max(origin_x);
+        pet.y = pet.y.min(origin_y + area_height - screen_height).max(origin_y);
+    }
+
+    println!("Pet size set to {}x{}", width, height);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_behavior_mode(state: State<AppState>, mode: BehaviorMode) {
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        // Perimeter walking moves gravity around, let a pet leaving it drop back to the floor
+        if pet.behavior_mode == BehaviorMode::PerimeterWalk && mode != BehaviorMode::PerimeterWalk {
+            pet.physics.gravity_direction = GravityDirection::Down;
+            pet.is_on_ground = false;
+            pet.perimeter_corner = None;
+        }
+        pet.behavior_mode = mode;
+    }
+
+    println!("Behavior mode set to: {:?}", mode);
+}
+
+// Give the pets a spot to live at. Idle pets wander back there instead of
+// off around the screen. The point is clamped into the window.
+#[tauri::command]
+fn set_home(state: State<AppState>, x: f32, y: f32) -> Result<(), String> {
+    if !x.is_finite() || !y.is_finite() {
+        return Err(format!("Home must be a finite position, got ({}, {})", x, y));
+    }
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        let home = (x.clamp(0.0, pet.window_width), y.clamp(0.0, pet.window_height));
+        pet.home = Some(home);
+    }
+
+    println!("Pet home set to ({}, {})", x, y);
+    Ok(())
+}
+
+#[tauri::command]
+fn clear_home(state: State<AppState>) {
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.home = None;
+    }
+
+    println!("Pet home cleared, wandering freely");
+}
+
+#[tauri::command]
+fn set_idle_fade(
+    state: State<AppState>,
+    after_seconds: f32,
+    rate: f32,
+    min_opacity: f32,
+) -> Result<(), String> {
+    BehaviorConfig::check_idle_fade(after_seconds, rate, min_opacity)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.fade_after_idle = after_seconds;
+        pet.behavior.fade_rate = rate;
+        pet.behavior.min_opacity = min_opacity;
+    }
+
+    println!(
+        "Idle fade set to {}/s down to {} after {}s",
+        rate, min_opacity, after_seconds
+    );
+    Ok(())
+}
+
+#[tauri::command]
+fn set_attractor(state: State<AppState>, x: f32, y: f32, strength: f32) -> Result<(), String> {
+    if !x.is_finite() || !y.is_finite() {
+        return Err(format!("Attractor must be a finite position, got ({}, {})", x, y));
+    }
+    if !(0.0..=MAX_ATTRACTOR_STRENGTH).contains(&strength) {
+        return Err(format!(
+            "Attractor strength must be between 0 and {}, got {}",
+            MAX_ATTRACTOR_STRENGTH, strength
+        ));
+    }
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.attractor = Some(Attractor { x, y, strength });
+        pet.wake();
+    }
+
+    println!("Attractor set at ({}, {}) with strength {}", x, y, strength);
+    Ok(())
+}
+
+#[tauri::command]
+fn clear_attractor(state: State<AppState>) {
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.attractor = None;
+    }
+
+    println!("Attractor cleared");
+}
+
+#[tauri::command]
+fn set_reduced_motion(state: State<AppState>, enabled: bool) {
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.reduced_motion = enabled;
+        if enabled {
+            pet.is_on_ceiling = false; // let go and come down gently
+            pet.burst_timer = 0.0;
+        }
+    }
+
+    println!("Reduced motion set to: {}", enabled);
+}
+
+#[tauri::command]
+fn set_face_away_from_walls(state: State<AppState>, enabled: bool) {
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.face_away_from_walls = enabled;
+    }
+
+    println!("Facing away from walls set to: {}", enabled);
+}
+
+#[tauri::command]
+fn set_idle_breathing(state: State<AppState>, enabled: bool) {
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.idle_breathing = enabled;
+    }
+
+    println!("Idle breathing set to: {}", enabled);
+}
+
+#[tauri::command]
+fn set_min_ground_dwell(state: State<AppState>, seconds: f32) -> Result<(), String> {
+    BehaviorConfig::check_ground_dwell(seconds)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.min_ground_dwell = seconds;
+    }
+
+    println!("Minimum ground dwell set to {}s", seconds);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_energy_bursts(
+    state: State<AppState>,
+    per_minute: f32,
+    duration: f32,
+    intensity: f32,
+) -> Result<(), String> {
+    BehaviorConfig::check_energy_bursts(per_minute, duration, intensity)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.bursts_per_minute = per_minute;
+        pet.behavior.burst_duration = duration;
+        pet.behavior.burst_intensity = intensity;
+    }
+
+    println!(
+        "Energy bursts set to {} a minute, {}s long, {}x jumping",
+        per_minute, duration, intensity
+    );
+    Ok(())
+}
+
+#[tauri::command]
+fn set_sleep_threshold(state: State<AppState>, seconds: f32) -> Result<(), String> {
+    BehaviorConfig::check_sleep_threshold(seconds)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.sleep_after_idle = seconds;
+    }
+
+    println!("Sleep threshold set to {}s", seconds);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_flee_radius(state: State<AppState>, radius: f32) -> Result<(), String> {
+    BehaviorConfig::check_flee_radius(radius)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.flee_radius = radius;
+    }
+
+    println!("Flee radius set to {}px", radius);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_wander_weights(
+    state: State<AppState>,
+    walk: f32,
+    run: f32,
+    jump: f32,
+) -> Result<(), String> {
+    BehaviorConfig::check_wander_weights(walk, run, jump)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.walk_weight = walk;
+        pet.behavior.run_weight = run;
+        pet.behavior.jump_weight = jump;
+    }
+
+    println!("Wander weights set to walk {}, run {}, jump {}", walk, run, jump);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_wander_target_config(
+    state: State<AppState>,
+    acceleration: f32,
+    arrive_radius: f32,
+    pause: f32,
+) -> Result<(), String> {
+    BehaviorConfig::check_wander_target(acceleration, arrive_radius, pause)?;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.target_acceleration = acceleration;
+        pet.behavior.target_arrive_radius = arrive_radius;
+        pet.behavior.target_pause = pause;
+    }
+
+    println!(
+        "Wander target set to acceleration {}, arrive radius {}, pause {}s",
+        acceleration, arrive_radius, pause
+    );
+    Ok(())
+}
+
+#[tauri::command]
+fn list_personalities() -> Vec<&'static str> {
+    PERSONALITIES.iter().map(|profile| profile.name).collect()
+}
+
+#[tauri::command]
+fn apply_personality(state: State<AppState>, name: String) -> Result<(), String> {
+    let Some(profile) = PERSONALITIES
+        .iter()
+        .find(|profile| profile.name.eq_ignore_ascii_case(&name))
+    else {
+        return Err(format!("Unknown personality \"{}\"", name));
+    };
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.physics.gravity = profile.gravity;
+        pet.physics.jump_force = profile.jump_force;
+        pet.physics.max_speed_x = profile.max_speed_x;
+        pet.behavior.set_jump_probability(profile.jump_probability);
+        pet.behavior.sleep_after_idle = profile.sleep_after_idle;
+        pet.behavior_mode = profile.behavior_mode;
+    }
+
+    println!("Personality set to {}", profile.name);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_jump_probability(state: State<AppState>) -> f64 {
+    let pets = state.lock_pets();
+    pets[0].behavior.jump_probability() as f64
+}
+
+// 0.0 keeps the pet on the ground (handy for screenshots), 1.0 makes it jump every time
+#[tauri::command]
+fn set_jump_probability(state: State<AppState>, p: f64) -> Result<(), String> {
+    if p.is_nan() {
+        return Err("Jump probability must be a number".to_string());
+    }
+    let probability = p.clamp(0.0, 1.0) as f32;
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.behavior.set_jump_probability(probability);
+    }
+
+    println!("Jump probability set to {}", probability);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_click_through(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window(MAIN_WINDOW_LABEL)
+        .ok_or_else(|| "Main window not found".to_string())?;
+
+    let result = apply_click_through(&window, enabled);
+
+    match &result {
+        Ok(()) => println!("Click-through set to: {}", enabled),
+        Err(e) => println!("{}", e),
+    }
+    result
+}
+
+// Every click-through toggle goes through here. On Wayland the Tauri call can
+// fail while the GTK input region still works, and that region has to be
+// cleared again when click-through goes off or the pet can't be grabbed.
+fn apply_click_through(window: &tauri::WebviewWindow, enabled: bool) -> Result<(), String> {
+    let result = window
+        .set_ignore_cursor_events(enabled)
+        .map_err(|e| format!("Failed to set click-through: {:?}", e));
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    if is_wayland_session() && set_wayland_click_through(window, enabled) {
+        return Ok(());
+    }
+
+    result
+}
+
+// Cover the whole virtual desktop instead of just the primary monitor,
+// so the pet can wander onto a second screen
+#[tauri::command]
+fn set_span_all_monitors(app: tauri::AppHandle, state: State<AppState>, enabled: bool) -> Result<(), String> {
+    state.span_all_monitors.store(enabled, Ordering::Relaxed);
+    state.pin_monitor(None);
+
+    match app.get_webview_window(MAIN_WINDOW_LABEL) {
+        Some(window) => fit_window_to_screen(&window, enabled),
+        None => Ok(()),
+    }
+}
+
+#[tauri::command]
+fn list_monitors(app: tauri::AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    let window = app
+        .get_webview_window(MAIN_WINDOW_LABEL)
+        .ok_or_else(|| "Main window not found".to_string())?;
+
+    list_monitor_info(&window)
+}
+
+#[tauri::command]
+fn move_to_monitor(app: tauri::AppHandle, state: State<AppState>, index: usize) -> Result<(), String> {
+    let window = app
+        .get_webview_window(MAIN_WINDOW_LABEL)
+        .ok_or_else(|| "Main window not found".to_string())?;
+
+    let (window_width, window_height) = fit_window_to_monitor(&window, index)?;
+    state.span_all_monitors.store(false, Ordering::Relaxed);
+    state.pin_monitor(Some(index));
+
+    let mut pets = state.lock_pets();
+    for pet in pets.iter_mut() {
+        pet.fit_to_window(window_width, window_height);
+    }
+
+    Ok(())
+}
+
+// Only changes the window level, click-through is left as it is
+#[tauri::command]
+fn set_always_on_top(app: tauri::AppHandle, state: State<AppState>, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window(MAIN_WINDOW_LABEL)
+        .ok_or_else(|| "Main window not found".to_string())?;
+
+    window
+        .set_always_on_top(enabled)
+        .map_err(|e| format!("Failed to set always on top: {:?}", e))?;
+    state.always_on_top.store(enabled, Ordering::Relaxed);
+
+    println!("Always on top set to: {}", enabled);
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn get_cursor_position(_window: &tauri::WebviewWindow) -> Option<(f32, f32)> {
+    unsafe {
+        let mut point = POINT { x: 0, y: 0 };
+
+        if GetCursorPos(&mut point).as_bool() {
+            Some((point.x as f32, point.y as f32))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn get_cursor_position(window: &tauri::WebviewWindow) -> Option<(f32, f32)> {
+    window
+        .cursor_position()
+        .ok()
+        .map(|position| (position.x as f32, position.y as f32))
+}
+
+// Seconds since the last keyboard or mouse input anywhere on the system, not
+// just over the pet. None where there's no way to ask.
+#[cfg(target_os = "windows")]
+fn system_idle_seconds() -> Option<f32> {
+    unsafe {
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+
+        if GetLastInputInfo(&mut info).as_bool() {
+            // Both count milliseconds since boot and wrap around together
+            Some(GetTickCount().wrapping_sub(info.dwTime) as f32 / 1000.0)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn system_idle_seconds() -> Option<f32> {
+    // kCGEventSourceStateCombinedSessionState and kCGAnyInputEventType
+    const COMBINED_SESSION_STATE: i32 = 0;
+    const ANY_INPUT_EVENT: u32 = u32::MAX;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceSecondsSinceLastEventType(state: i32, event_type: u32) -> f64;
+    }
+
+    let seconds = unsafe { CGEventSourceSecondsSinceLastEventType(COMBINED_SESSION_STATE, ANY_INPUT_EVENT) };
+    seconds.is_finite().then_some(seconds as f32)
+}
+
+// X11 and Wayland each need their own extension for this, so the pets just
+// don't nap on Linux for now
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn system_idle_seconds() -> Option<f32> {
+    None
+}
+
+// Cursor position converted from screen pixels into the window's logical
+// coordinates, which is the space the pet's x/y live in
+fn cursor_in_window(window: &tauri::WebviewWindow) -> Option<(f32, f32)> {
+    let (cursor_x, cursor_y) = get_cursor_position(window)?;
+    let origin = window.inner_position().ok()?;
+    let scale = window.scale_factor().unwrap_or(1.0) as f32;
+
+    Some((
+        (cursor_x - origin.x as f32) / scale,
+        (cursor_y - origin.y as f32) / scale,
+    ))
+}
+
+fn logical_window_size(window: &tauri::WebviewWindow) -> Option<(f32, f32)> {
+    let size = window.inner_size().ok()?;
+    let scale = window.scale_factor().unwrap_or(1.0) as f32;
+
+    Some((size.width as f32 / scale, size.height as f32 / scale))
+}
+
+// Advance the physics at a fixed rate and push the result to the frontend,
+// so it doesn't need to poll get_pet_movement every frame
+fn spawn_physics_tick(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut next_tick = Instant::now();
+        let mut all_resting = false;
+        let mut last_on_top_reassert = Instant::now();
+        let mut last_visibility_check = Instant::now();
+        let mut window_hidden = false;
+        let mut last_afk_check = Instant::now();
+
+        loop {
+            let tick_hz = app_handle
+                .try_state::<AppState>()
+                .map_or(DEFAULT_TICK_HZ, |state| state.tick_hz());
+            let tick_hz = if all_resting || window_hidden { tick_hz.min(IDLE_TICK_HZ) } else { tick_hz };
+
+            // Sleep until the next tick is due. If a frame ran long, carry on
+            // from now rather than firing a burst of ticks to catch up; update
+            // works off the real elapsed time anyway.
+            next_tick += std::time::Duration::from_secs_f32(1.0 / tick_hz as f32);
+            let now = Instant::now();
+            if next_tick > now {
+                std::thread::sleep(next_tick - now);
+            } else {
+                next_tick = now;
+            }
+
+            // The window is gone once the app is closing, stop ticking
+            let Some(window) = app_handle.get_webview_window(MAIN_WINDOW_LABEL) else {
+                break;
+            };
+
+            let Some((window_width, window_height)) = logical_window_size(&window) else {
+                continue;
+            };
+
+            let cursor = cursor_in_window(&window);
+
+            let Some(state) = app_handle.try_state::<AppState>() else {
+                continue;
+            };
+            state.tick_timings().record(Instant::now());
+
+            // No window event covers every way of hiding a window (minimizing,
+            // the tray, another app hiding it), so ask now and then
+            if last_visibility_check.elapsed() >= VISIBILITY_CHECK {
+                last_visibility_check = Instant::now();
+                let visible = window.is_visible().unwrap_or(true);
+                let minimized = window.is_minimized().unwrap_or(false);
+                state.set_window_hidden(!visible || minimized);
+            }
+            window_hidden = state.is_window_hidden();
+
+            // Sleeping pets count as resting, so napping also drops the tick rate
+            if last_afk_check.elapsed() >= AFK_CHECK && !state.is_paused() {
+                last_afk_check = Instant::now();
+                state.check_afk_nap();
+            }
+
+            if state.is_always_on_top() && last_on_top_reassert.elapsed() >= ALWAYS_ON_TOP_REASSERT {
+                last_on_top_reassert = Instant::now();
+                if let Err(e) = window.set_always_on_top(true) {
+                    println!("Failed to re-assert always on top: {:?}", e);
+                }
+            }
+
+            let (payload, events) = {
+                let mut pets = state.lock_pets();
+                if state.is_shutting_down() {
+                    break;
+                }
+                if !state.is_paused() && !window_hidden {
+                    let now = Instant::now();
+                    for pet in pets.iter_mut() {
+                        pet.update(window_width, window_height, cursor, now);
+                    }
+                    resolve_pet_collisions(&mut pets);
+                }
+                let events = take_physics_events(&mut pets);
+
+                all_resting = pets.iter().all(PetState::is_resting);
+                // Every pet in one event, like get_all_snapshots. The first
+                // is the primary pet, the one the mouse and commands act on.
+                let snapshots: Vec<PetSnapshot> = pets.iter().map(PetState::snapshot).collect();
+                (snapshots, events)
+            };
+
+            if let Err(e) = window.emit("pet-update", payload) {
+                println!("Failed to emit pet update, stopping physics tick: {:?}", e);
+                break;
+            }
+
+            emit_physics_events(&window, events);
+        }
+    });
+}
+
+// Every pet's events from its latest update, tagged with the pet's id
+fn take_physics_events(pets: &mut [PetState]) -> Vec<(usize, PhysicsEvent)> {
+    let mut events = Vec::new();
+    for pet in pets.iter_mut() {
+        events.extend(pet.events.drain(..).map(|event| (pet.id, event)));
+    }
+    events
+}
+
+fn emit_physics_events(window: &tauri::WebviewWindow, events: Vec<(usize, PhysicsEvent)>) {
+    for (id, event) in events {
+        let (name, speed) = match event {
+            PhysicsEvent::Jumped(speed) => ("pet-jumped", speed),
+            PhysicsEvent::Landed(speed) => ("pet-landed", speed),
+        };
+        let _ = window.emit(name, PhysicsEventPayload { id, speed });
+    }
+}
+
+// Bounding rectangle of every connected monitor, in physical pixels. Monitors
+// left of or above the primary one have negative origins, so this can too.
+fn virtual_desktop_rect(
+    window: &tauri::WebviewWindow,
+) -> Option<(tauri::PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    let monitors = window.available_monitors().ok()?;
+
+    let mut left = i32::MAX;
+    let mut top = i32::MAX;
+    let mut right = i32::MIN;
+    let mut bottom = i32::MIN;
+
+    for monitor in &monitors {
+        let position = monitor.position();
+        let size = monitor.size();
+
+        left = left.min(position.x);
+        top = top.min(position.y);
+        right = right.max(position.x + size.width as i32);
+        bottom = bottom.max(position.y + size.height as i32);
+    }
+
+    if monitors.is_empty() {
+        return None;
+    }
+
+    Some((
+        tauri::PhysicalPosition::new(left, top),
+        PhysicalSize::new((right - left) as u32, (bottom - top) as u32),
+    ))
+}
+
+// Size the overlay window to the screen. The pets pick the new bounds up on
+// the next physics tick, since update reads the window size every frame.
+#[allow(unexpected_cfgs)]
+fn fit_window_to_screen(window: &tauri::WebviewWindow, span_all_monitors: bool) -> Result<(), String> {
+    if span_all_monitors {
+        if let Some((position, size)) = virtual_desktop_rect(window) {
+            window
+                .set_size(size)
+                .map_err(|e| format!("Failed to resize window: {:?}", e))?;
+
+            window
+                .set_position(tauri::Position::Physical(position))
+                .map_err(|e| format!("Failed to position window: {:?}", e))?;
+
+            println!(
+                "Spanning all monitors: {}x{} at ({}, {})",
+                size.width, size.height, position.x, position.y
+            );
+            return Ok(());
+        }
+
+        println!("Could not read the monitor layout, falling back to the primary monitor");
+    }
+
+    // Resize window based on platform
+    #[cfg(target_os = "windows")]
+    {
+        unsafe {
+            // Get the work area (screen size excluding taskbar)
+            let mut work_area = RECT::default();
+            let found = SystemParametersInfoW(
+                SPI_GETWORKAREA,
+                0,
+                Some(&mut work_area as *mut _ as *mut std::ffi::c_void),
+                windows::Win32::UI::WindowsAndMessaging::SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+            )
+            .as_bool();
+
+            // Calculate work area dimensions
+            let width = work_area.right - work_area.left;
+            let height = work_area.bottom - work_area.top;
+
+            // A failed call leaves the RECT zeroed, and casting a negative
+            // size to u32 would wrap around to a huge window
+            if !found || width <= 0 || height <= 0 {
+                println!(
+                    "Windows: invalid work area {}x{} (lookup succeeded: {}), falling back to the primary monitor",
+                    width, height, found
+                );
+            } else {
+                const BORDER_FIX: i32 = 8;
+
+                // Set window size to match work area
+                window
+                    .set_size(PhysicalSize::new(width as u32, height as u32))
+                    .map_err(|e| format!("Failed to resize window: {:?}", e))?;
+
+                // Position at the top-left corner of the work area
+                window
+                    .set_position(tauri::Position::Physical(tauri::PhysicalPosition::new(
+                        work_area.left + BORDER_FIX,
+                        work_area.top,
+                    )))
+                    .map_err(|e| format!("Failed to position window: {:?}", e))?;
+
+                println!("Windows: Configured to work area {}x{} at ({}, {})", 
+                    width, height, work_area.left, work_area.top);
+                return Ok(());
+            }
+        }
+    }
+
+    // For non-Windows platforms, or if Windows has no usable work area, use the full screen
+    {
+        if let Some(monitor) = window
+            .primary_monitor()
+            .map_err(|e| format!("Failed to get monitors: {:?}", e))?
+        {
+            let size = monitor.size();
+
+            window
+                .set_size(PhysicalSize::new(size.width, size.height))
+                .map_err(|e| format!("Failed to resize window: {:?}", e))?;
+
+            window
+                .set_position(tauri::Position::Physical(tauri::PhysicalPosition::new(
+                    0, 0,
+                )))
+                .map_err(|e| format!("Failed to position window: {:?}", e))?;
+
+            println!(
+                "Resized window to match monitor: {}x{}",
+                size.width, size.height
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// A display as the settings panel sees it, in physical pixels. The work area
+// leaves out the taskbar / dock / menu bar.
+#[derive(Debug, Clone, Serialize)]
+struct MonitorInfo {
+    index: usize,
+    name: Option<String>,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    work_x: i32,
+    work_y: i32,
+    work_width: u32,
+    work_height: u32,
+    scale_factor: f64,
+    is_primary: bool,
+}
+
+fn list_monitor_info(window: &tauri::WebviewWindow) -> Result<Vec<MonitorInfo>, String> {
+    let monitors = window
+        .available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {:?}", e))?;
+    let primary = window.primary_monitor().ok().flatten();
+
+    Ok(monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| {
+            let position = *monitor.position();
+            let size = *monitor.size();
+            let work_area = monitor.work_area();
+
+            MonitorInfo {
+                index,
+                name: monitor.name().cloned(),
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                work_x: work_area.position.x,
+                work_y: work_area.position.y,
+                work_width: work_area.size.width,
+                work_height: work_area.size.height,
+                scale_factor: monitor.scale_factor(),
+                is_primary: primary
+                    .as_ref()
+                    .is_some_and(|primary| *primary.position() == position && *primary.size() == size),
+            }
+        })
+        .collect())
+}
+
+// Cover one monitor's work area. Returns the new size in logical pixels, which
+// is what the pets measure their window in.
+fn fit_window_to_monitor(window: &tauri::WebviewWindow, index: usize) -> Result<(f32, f32), String> {
+    let monitors = window
+        .available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {:?}", e))?;
+    let Some(monitor) = monitors.get(index) else {
+        return Err(format!(
+            "No monitor {}, there {} {}",
+            index,
+            if monitors.len() == 1 { "is" } else { "are" },
+            monitors.len()
+        ));
+    };
+
+    // Per-monitor work area, on Windows this is the same rectangle
+    // SPI_GETWORKAREA reports for the primary display
+    let work_area = monitor.work_area();
+    window
+        .set_size(work_area.size)
+        .map_err(|e| format!("Failed to resize window: {:?}", e))?;
+    window
+        .set_position(tauri::Position::Physical(work_area.position))
+        .map_err(|e| format!("Failed to position window: {:?}", e))?;
+
+    println!(
+        "Moved window to monitor {}: {}x{} at ({}, {})",
+        index, work_area.size.width, work_area.size.height, work_area.position.x, work_area.position.y
+    );
+
+    let scale = monitor.scale_factor() as f32;
+    Ok((work_area.size.width as f32 / scale, work_area.size.height as f32 / scale))
 }
 
-struct AppState {
-    pet: Mutex<PetState>,
+// Position and size of every monitor, used to notice when displays are
+// plugged in, unplugged, rearranged or change resolution
+fn monitor_layout(window: &tauri::WebviewWindow) -> Option<Vec<(i32, i32, u32, u32)>> {
+    let monitors = window.available_monitors().ok()?;
+
+    Some(
+        monitors
+            .iter()
+            .map(|monitor| {
+                let position = monitor.position();
+                let size = monitor.size();
+                (position.x, position.y, size.width, size.height)
+            })
+            .collect(),
+    )
 }
 
+// Tauri has no monitor-changed event, so poll the layout and refit the window
+// whenever it changes. The pets get the new bounds on their next update.
+fn spawn_monitor_watch(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_layout = None;
 
-#[tauri::command]
-fn get_pet_movement(
-    state: State<AppState>,
-    window_width: f32,
-    window_height: f32,
-) -> (f32, f32, String) {
-    let mut pet = state.pet.lock().unwrap();
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(2));
 
-    // Update pet with the current window dimensions
-    pet.update(window_width, window_height);
+            if app_handle.try_state::<AppState>().is_some_and(|state| state.is_shutting_down()) {
+                break;
+            }
+            let Some(window) = app_handle.get_webview_window(MAIN_WINDOW_LABEL) else {
+                break;
+            };
 
-    (pet.x, pet.y, pet.animation_state.to_string().to_string())
-}
+            let Some(layout) = monitor_layout(&window) else {
+                continue;
+            };
 
-#[tauri::command]
-fn pet_pet(state: State<AppState>) {
-    let mut pet = state.pet.lock().unwrap();
+            if last_layout.as_ref().is_some_and(|last| *last != layout) {
+                println!("Monitor layout changed, refitting window");
 
-    let was_already_loved = pet.love_timer > 0.0;
+                let state = app_handle.try_state::<AppState>();
+                let pinned = state.as_ref().and_then(|state| state.pinned_monitor());
+                let span_all_monitors = state.is_some_and(|state| state.spans_all_monitors());
 
-    pet.love_timer = 3.0;
+                // Stay on the chosen display if it is still there
+                let refit = pinned.map(|index| fit_window_to_monitor(&window, index));
+                if !matches!(refit, Some(Ok(_))) {
+                    if let Err(e) = fit_window_to_screen(&window, span_all_monitors) {
+                        println!("Failed to refit the window to the new layout: {}", e);
+                    }
+                }
+            }
 
-    
+            last_layout = Some(layout);
+        }
+    });
+}
 
-    let affection_gain = match pet.emotion_state(){
-        EmotionState::Lonely => 8.0,
-        EmotionState::Neutral => 5.0,
-        EmotionState::Happy => 3.0,
-        EmotionState::Excited => 1.5,
+// While the shortcut is held the whole window takes the mouse, so it can be
+// dragged somewhere else. Letting go goes back to clicking through.
+fn set_window_drag_mode(app: &tauri::AppHandle, enabled: bool) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
     };
 
-    pet.needs.affection = (pet.needs.affection + affection_gain).min(100.0);
+    if state.window_drag_mode.swap(enabled, Ordering::Relaxed) == enabled {
+        // Key repeat sends Pressed over and over, only act on the change
+        return;
+    }
 
-    println!("Affection: {}", pet.needs.affection);
-    pet.velocity_x = 0.0;
-    pet.velocity_y = 0.0;
-    pet.current_action = PetAction::Idling;
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+        if let Err(e) = apply_click_through(&window, !enabled) {
+            println!("{} for window drag", e);
+        }
 
-    if !was_already_loved {
-        pet.choose_idle_animation();
+        let _ = window.emit("window-drag-mode", enabled);
     }
+
+    println!("Window drag mode: {}", enabled);
 }
 
-#[tauri::command]
-fn get_pet_stats(state: State<AppState>) -> (f32, f32, f32, String) {
-    let pet = state.pet.lock().unwrap();
+// Platform-specific setup to make the window click-through. Errors are
+// returned so setup can decide whether the app is usable without it.
+fn setup_window_properties(window: &tauri::WebviewWindow) -> Result<(), tauri::Error> {
+    #[cfg(target_os = "macos")]
+    {
+        let ns_window = window.ns_window()?;
+        if !set_macos_ignores_mouse_events(ns_window, true) {
+            // No NSWindow to talk to directly, let Tauri try
+            window.set_ignore_cursor_events(true)?;
+        }
+        println!("macOS: Set window to ignore mouse events");
+    }
 
+    #[cfg(target_os = "windows")]
+    {
+        // Use Tauri's built-in function instead of direct Win32 API calls
+        window.set_ignore_cursor_events(true)?;
+        println!("Windows: Set window to ignore mouse events");
+    }
 
-    let mood = match pet.emotion_state() {
-        EmotionState::Lonely => "Lonely",
-        EmotionState::Neutral => "Neutral",
-        EmotionState::Happy => "Happy",
-        EmotionState::Excited => "Excited",
-    };
-    (
-        pet.needs.affection,
-        pet.needs.hunger,
-        pet.needs.energy,
-        mood.to_string(),
-    )
-}
+    // For Linux and other platforms, we rely on the standard Tauri API
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let result = window.set_ignore_cursor_events(true);
+        match &result {
+            Ok(()) => println!("Set window to ignore cursor events"),
+            Err(e) => println!("Failed to set ignore cursor events: {:?}", e),
+        }
 
-#[tauri::command]
-fn reset_pet_position(
-    state: State<AppState>,
-    window_width: f32,
-    window_height: f32,
-) -> (f32, f32, String) {
-    let mut pet = state.pet.lock().unwrap();
-    *pet = PetState::new(window_width, window_height);
-    (pet.x, pet.y, pet.animation_state.to_string().to_string())
-}
+        // Some Wayland compositors silently ignore the request above, so also
+        // give the GTK surface an empty input region
+        let wayland_ok = is_wayland_session() && set_wayland_click_through(window, true);
 
-#[tauri::command]
-fn set_click_through(app: tauri::AppHandle, enabled: bool) {
-    if let Some(window) = app.get_webview_window("main") {
-        if let Err(e) = window.set_ignore_cursor_events(enabled) {
-            println!("Failed to set click-through: {:?}", e);
-        } else {
-            println!("Click-through set to: {}", enabled);
+        if !wayland_ok {
+            result?;
         }
     }
+
+    Ok(())
 }
 
-#[cfg(target_os = "windows")]
-fn get_cursor_position() -> Option<(f32, f32)> {
-    unsafe {
-        let mut point = POINT { x: 0, y: 0 };
+// "transparent" in tauri.conf.json covers Windows and macOS, but on GTK the
+// window only gets an alpha channel if it has an RGBA visual, otherwise GNOME
+// (Wayland and X11 alike) paints the background black
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn setup_window_transparency(window: &tauri::WebviewWindow) {
+    use gtk::prelude::WidgetExt;
 
-        if GetCursorPos(&mut point).as_bool() {
-            Some((point.x as f32, point.y as f32))
-        } else {
-            None
+    let session = if is_wayland_session() { "Wayland" } else { "X11" };
+
+    let gtk_window = match window.gtk_window() {
+        Ok(gtk_window) => gtk_window,
+        Err(e) => {
+            println!("{}: failed to get the GTK window for transparency: {:?}", session, e);
+            return;
+        }
+    };
+
+    gtk_window.set_app_paintable(true);
+
+    match gtk_window.screen() {
+        Some(screen) => {
+            match screen.rgba_visual() {
+                Some(visual) => {
+                    gtk_window.set_visual(Some(&visual));
+                    println!("{}: applied an RGBA visual and app-paintable surface", session);
+                }
+                None => println!("{}: no RGBA visual available, the window may not be transparent", session),
+            }
+
+            if !screen.is_composited() {
+                println!("{}: no compositor is running, transparency won't work", session);
+            }
         }
+        None => println!("{}: the GTK window has no screen, skipping the RGBA visual", session),
     }
 }
 
-// Platform-specific window setup
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn setup_window_transparency(_window: &tauri::WebviewWindow) {
+    println!("Window transparency comes from the window config");
+}
+
+// ns_window() hands out whatever NSWindow currently backs the Tauri window,
+// which is null while AppKit is tearing it down or re-creating it
+#[cfg(target_os = "macos")]
 #[allow(unexpected_cfgs)]
-fn setup_window_properties(window: &tauri::WebviewWindow) {
-    // Set up click-through functionality based on platform
+fn set_macos_ignores_mouse_events(ns_window: *mut std::ffi::c_void, ignore: bool) -> bool {
+    if ns_window.is_null() {
+        println!("macOS: no NSWindow to set ignore mouse events on");
+        return false;
+    }
 
-    #[cfg(target_os = "macos")]
+    let ns_window = ns_window as cocoa::base::id;
     unsafe {
-        if let Ok(ns_window) = window.ns_window() {
-            let ns_window = ns_window as cocoa::base::id;
-            let _: () = msg_send![ns_window, setIgnoresMouseEvents: true];
-            println!("macOS: Set window to ignore mouse events");
-        }
+        let _: () = msg_send![ns_window, setIgnoresMouseEvents: ignore];
     }
+    true
+}
+
+// After the window moves to another display AppKit sometimes starts handing
+// it clicks again even though ignoresMouseEvents still reads YES. Flipping the
+// flag off and back on makes it take effect again.
+#[cfg(target_os = "macos")]
+#[allow(unexpected_cfgs)]
+fn reapply_macos_click_through(window: &tauri::Window) {
+    static LAST_MONITOR: Mutex<Option<String>> = Mutex::new(None);
+
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned());
 
-    #[cfg(target_os = "windows")]
     {
-        // Use Tauri's built-in function instead of direct Win32 API calls
-        if let Err(e) = window.set_ignore_cursor_events(true) {
-            println!("Failed to set ignore cursor events: {:?}", e);
-        } else {
-            println!("Windows: Set window to ignore mouse events");
+        let mut last_monitor = LAST_MONITOR.lock().unwrap_or_else(PoisonError::into_inner);
+        if *last_monitor == monitor {
+            return;
         }
+        *last_monitor = monitor.clone();
     }
 
-    // For Linux and other platforms, we rely on the standard Tauri API
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let Ok(ns_window) = window.ns_window() else {
+        println!("macOS: could not get the NSWindow to re-apply click-through");
+        return;
+    };
+    if ns_window.is_null() {
+        return;
+    }
+
+    let ignoring: cocoa::base::BOOL =
+        unsafe { msg_send![ns_window as cocoa::base::id, ignoresMouseEvents] };
+    let ignoring = ignoring != cocoa::base::NO;
+
+    if set_macos_ignores_mouse_events(ns_window, !ignoring)
+        && set_macos_ignores_mouse_events(ns_window, ignoring)
     {
-        if let Err(e) = window.set_ignore_cursor_events(true) {
-            println!("Failed to set ignore cursor events: {:?}", e);
-        } else {
-            println!("Set window to ignore cursor events");
+        println!(
+            "macOS: window moved to display {:?}, re-applied ignore mouse events ({})",
+            monitor, ignoring
+        );
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn is_wayland_session() -> bool {
+    std::env::var("XDG_SESSION_TYPE")
+        .map(|session| session.eq_ignore_ascii_case("wayland"))
+        .unwrap_or(false)
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+// An empty input region lets every click through to the windows below; no
+// region restores normal input handling
+#[cfg(target_os = "linux")]
+fn set_wayland_click_through(window: &tauri::WebviewWindow, enabled: bool) -> bool {
+    use gtk::prelude::WidgetExt;
+
+    match window.gtk_window() {
+        Ok(gtk_window) => {
+            let empty_region = gtk::cairo::Region::create();
+            gtk_window.input_shape_combine_region(enabled.then_some(&empty_region));
+            println!("Wayland: set GTK input region click-through to {}", enabled);
+            true
+        }
+        Err(e) => {
+            println!("Wayland: failed to get the GTK window: {:?}", e);
+            false
         }
     }
 }
 
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn set_wayland_click_through(_window: &tauri::WebviewWindow, _enabled: bool) -> bool {
+    false
+}
+
 
 
 
@@ -637,87 +5219,169 @@ fn setup_window_properties(window: &tauri::WebviewWindow) {
 pub fn run() {
     println!("Starting desktop pet application");
 
+    let config = read_config_file().unwrap_or_default();
+    let (window_width, window_height) = initial_window_size(&config);
+    let pet_count = initial_pet_count(&config);
+
     tauri::Builder::default()
-        .manage(AppState {
-            pet: Mutex::new(PetState::new(1920.0, 1032.0)),
-        })
+        .manage(AppState::new(load_state(window_width, window_height, pet_count)))
         .invoke_handler(tauri::generate_handler![
             get_pet_movement,
+            get_pet_snapshot,
             reset_pet_position,
             set_click_through,
             pet_pet,
-            get_pet_stats
+            get_pet_stats,
+            set_physics_config,
+            grab_pet,
+            release_pet,
+            add_pet,
+            remove_pet,
+            get_all_pets_movement,
+            set_behavior_mode,
+            wake_pet,
+            set_sleep_threshold,
+            set_wander_weights,
+            set_pet_size,
+            set_restitution,
+            pause_pet,
+            resume_pet,
+            teleport_pet,
+            set_span_all_monitors,
+            set_air_drag,
+            set_ground_friction,
+            set_flee_radius,
+            get_jump_probability,
+            set_jump_probability,
+            is_pet_airborne,
+            set_tick_rate,
+            spawn_at_edge,
+            pet_clicked,
+            set_gravity_direction,
+            set_terminal_velocity,
+            list_animation_states,
+            get_pet_bounds,
+            set_facing,
+            set_confinement,
+            clear_confinement,
+            set_pet_skin,
+            apply_impulse,
+            set_ground_offset,
+            move_left,
+            move_right,
+            jump,
+            set_time_scale,
+            set_idle_breathing,
+            get_spawn_from_top,
+            set_spawn_from_top,
+            set_collision_response,
+            set_wander_target_config,
+            set_wind,
+            debug_dump,
+            list_personalities,
+            apply_personality,
+            predict_trajectory,
+            set_min_ground_dwell,
+            set_always_on_top,
+            get_behavior,
+            get_animation_metadata,
+            list_monitors,
+            move_to_monitor,
+            set_energy_bursts,
+            get_trail,
+            force_animation,
+            get_perf_stats,
+            set_face_away_from_walls,
+            set_home,
+            clear_home,
+            set_landing_event_filter,
+            set_afk_nap,
+            set_bounce_threshold,
+            export_state,
+            import_state,
+            set_idle_fade,
+            startle,
+            get_all_snapshots,
+            set_attractor,
+            clear_attractor,
+            is_pet_in_rect,
+            set_reduced_motion
         ])
+        .on_window_event(|window, event| {
+            #[cfg(target_os = "macos")]
+            if let tauri::WindowEvent::Moved(_) | tauri::WindowEvent::ScaleFactorChanged { .. } = event {
+                reapply_macos_click_through(window);
+            }
+
+            // Windows reports minimizing as a resize to nothing, catch that
+            // straight away instead of waiting for the tick to notice
+            if let tauri::WindowEvent::Resized(size) = event {
+                if let Some(state) = window.try_state::<AppState>() {
+                    if size.width == 0 || size.height == 0 {
+                        state.set_window_hidden(true);
+                    }
+                }
+            }
+
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                if let Some(state) = window.try_state::<AppState>() {
+                    state.shut_down();
+                }
+            }
+        })
         .setup(|app| {
             // Get the main window
-            if let Some(window) = app.get_webview_window("main") {
-                // Resize window based on platform
-                #[cfg(target_os = "windows")]
-                {
-                    unsafe {
-                        // Get the work area (screen size excluding taskbar)
-                        let mut work_area = RECT::default();
-                        SystemParametersInfoW(
-                            SPI_GETWORKAREA,
-                            0,
-                            Some(&mut work_area as *mut _ as *mut std::ffi::c_void),
-                            windows::Win32::UI::WindowsAndMessaging::SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
-                        );
-
-                        // Calculate work area dimensions
-                        let width = work_area.right - work_area.left;
-                        let height = work_area.bottom - work_area.top;
-
-                        const BORDER_FIX: i32 = 8;
-
-                        // Set window size to match work area
-                        window
-                            .set_size(PhysicalSize::new(width as u32, height as u32))
-                            .expect("Failed to resize window");
-
-                        // Position at the top-left corner of the work area
-                        window
-                            .set_position(tauri::Position::Physical(tauri::PhysicalPosition::new(
-                                work_area.left + BORDER_FIX,
-                                work_area.top,
-                            )))
-                            .expect("Failed to position window");
-
-                        println!("Windows: Configured to work area {}x{} at ({}, {})", 
-                            width, height, work_area.left, work_area.top);
-                    }
+            if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+                load_config_file(&app.state::<AppState>());
+
+                let span_all_monitors = app.state::<AppState>().spans_all_monitors();
+                if let Err(e) = fit_window_to_screen(&window, span_all_monitors) {
+                    println!("Could not fit the window to the screen: {}", e);
                 }
 
-                // For non-Windows platforms, use the full screen
-                #[cfg(not(target_os = "windows"))]
-                {
-                    if let Some(monitor) = window.primary_monitor().expect("Failed to get monitors")
-                    {
-                        let size = monitor.size();
-
-                        window
-                            .set_size(PhysicalSize::new(size.width, size.height))
-                            .expect("Failed to resize window");
-
-                        window
-                            .set_position(tauri::Position::Physical(tauri::PhysicalPosition::new(
-                                0, 0,
-                            )))
-                            .expect("Failed to position window");
-
-                        println!(
-                            "Resized window to match monitor: {}x{}",
-                            size.width, size.height
-                        );
+                setup_window_transparency(&window);
+                if let Err(e) = window.set_always_on_top(true) {
+                    println!("Failed to set the window always on top: {:?}", e);
+                }
+                if let Err(e) = setup_window_properties(&window) {
+                    // A window that eats every click over the desktop is worse than no pet
+                    // on Windows and macOS. On Linux some compositors just don't support it,
+                    // so carry on there.
+                    println!(
+                        "Could not make the pet window click-through, clicks on the \
+                         desktop behind it will be captured: {:?}",
+                        e
+                    );
+                    if cfg!(any(target_os = "macos", target_os = "windows")) {
+                        return Err(e.into());
                     }
                 }
 
-                setup_window_properties(&window);
-
                 window.show().expect("Failed to show window");
                 println!("Window is now visible and ready");
 
-                #[cfg(target_os = "windows")]
+                spawn_physics_tick(app.handle().clone());
+                spawn_monitor_watch(app.handle().clone());
+                spawn_config_watch(app.handle().clone());
+
+                // Hold Ctrl+Alt+M to move the pet window
+                let drag_shortcut = Shortcut::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyM);
+                app.handle().plugin(
+                    tauri_plugin_global_shortcut::Builder::new()
+                        .with_handler(move |app, shortcut, event| {
+                            if shortcut == &drag_shortcut {
+                                set_window_drag_mode(app, event.state() == ShortcutState::Pressed);
+                            }
+                        })
+                        .build(),
+                )?;
+
+                if let Err(e) = app.global_shortcut().register(drag_shortcut) {
+                    println!("Failed to register the window drag shortcut: {:?}", e);
+                }
+
+                // Only capture the mouse while it is over the pet (or dragging it),
+                // so clicks everywhere else fall through to the desktop
                 {
                     let app_handle = app.handle().clone();
 
@@ -727,37 +5391,42 @@ pub fn run() {
                         loop {
                             std::thread::sleep(std::time::Duration::from_millis(150));
 
-                            let Some(window) = app_handle.get_webview_window("main") else {
+                            let Some(window) = app_handle.get_webview_window(MAIN_WINDOW_LABEL) else {
                                 continue;
                             };
 
-                            let Some((cursor_x, cursor_y)) = get_cursor_position() else {
+                            let Some((cursor_x, cursor_y)) = cursor_in_window(&window) else {
                                 continue;
                             };
 
                             let Some(state) = app_handle.try_state::<AppState>() else {
                                 continue;
                             };
+                            if state.is_shutting_down() {
+                                break;
+                            }
+
+                            // The drag shortcut owns the mouse until it is released
+                            if state.in_window_drag_mode() {
+                                is_currently_click_through = false;
+                                continue;
+                            }
 
-                            let pet = state.pet.lock().unwrap();
+                            let pets = state.lock_pets();
 
-                            let cursor_over_pet =
-                                pet.is_cursor_over_pet(cursor_x, cursor_y);
+                            let cursor_over_pet = pets.iter().any(|pet| {
+                                pet.is_grabbed || pet.is_cursor_over_pet(cursor_x, cursor_y)
+                            });
 
-                            drop(pet);
+                            drop(pets);
 
                             let should_be_click_through = !cursor_over_pet;
 
                             if should_be_click_through != is_currently_click_through {
                                 if let Err(error) =
-                                    window.set_ignore_cursor_events(
-                                should_be_click_through
-                                    )
+                                    apply_click_through(&window, should_be_click_through)
                                 {
-                                    println!(
-                                        "Failed to toggle click-through: {:?}",
-                                        error
-                                    );
+                                    println!("{}", error);
                                 } else {
                                     is_currently_click_through =
                                         should_be_click_through;
@@ -770,29 +5439,39 @@ pub fn run() {
                 }
 
             } else {
-                println!("Warning: Could not find main window");
+                // Without the window there is nothing to show, don't keep running headless
+                return Err(format!(
+                    "No \"{}\" window, check the window label in tauri.conf.json",
+                    MAIN_WINDOW_LABEL
+                )
+                .into());
             }
             
+            let show = MenuItem::with_id(app, "show", "Show Pet", true, None::<&str>)?;
             let settings = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
             let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&settings, &quit])?;
+            let menu = Menu::with_items(app, &[&show, &settings, &quit])?;
 
             let _tray = TrayIconBuilder::with_id("main-tray")
                 .icon(app.default_window_icon().unwrap().clone())
                 .menu(&menu)
                 .on_menu_event(|app, event| {
                     match event.id.as_ref(){
+                        "show" => {
+                            println!("Show Pet clicked from tray");
+                            show_from_tray(app);
+                        }
                         "settings"=> {
                             println!("Settings clicked from tray");
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.set_ignore_cursor_events(false);
+                            if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+                                let _ = apply_click_through(&window, false);
                                 let _ = window.emit("open-settings", ());
                                 let _ = window.set_focus();
                             }
                         }
                         "quit" => {
                             
-                            if let Some(window) = app.get_webview_window("main") {
+                            if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
                                 let _ = window.close();
                             }
 
@@ -815,3 +5494,411 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running Tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const WIDTH: f32 = 800.0;
+    const HEIGHT: f32 = 600.0;
+
+    // A seeded pet that only moves when the physics moves it, with its first
+    // (zero-length) frame already taken at the returned time. Tests move that
+    // time forward instead of sleeping.
+    fn physics_pet(window_width: f32, window_height: f32) -> (PetState, Instant) {
+        let mut pet = PetState::with_seed(7, window_width, window_height);
+        pet.behavior_mode = BehaviorMode::ManualControl;
+
+        let start = Instant::now();
+        pet.update(window_width, window_height, None, start);
+        (pet, start)
+    }
+
+    fn advance(pet: &mut PetState, window_width: f32, window_height: f32, now: &mut Instant, seconds: f32) {
+        *now += Duration::from_secs_f32(seconds);
+        pet.update(window_width, window_height, None, *now);
+    }
+
+    fn assert_in_bounds(pet: &PetState, window_width: f32, window_height: f32) {
+        assert!(pet.x >= 0.0 && pet.x + pet.pet_width <= window_width, "x out of bounds: {}", pet.x);
+        assert!(pet.y >= 0.0 && pet.y + pet.pet_height <= window_height, "y out of bounds: {}", pet.y);
+    }
+
+    #[test]
+    fn long_gap_between_frames_stays_in_bounds() {
+        let (mut pet, mut now) = physics_pet(WIDTH, HEIGHT);
+        pet.is_on_ground = false;
+        pet.velocity_x = 1000.0;
+        pet.velocity_y = -1000.0;
+        let start_x = pet.x;
+
+        advance(&mut pet, WIDTH, HEIGHT, &mut now, 5.0);
+
+        assert_in_bounds(&pet, WIDTH, HEIGHT);
+        // Only one capped frame's worth of movement
+        assert!((pet.x - start_x).abs() <= 1000.0 * MAX_DELTA_TIME);
+    }
+
+    #[test]
+    fn shrinking_window_pulls_pet_in_the_same_frame() {
+        let (mut pet, mut now) = physics_pet(WIDTH, HEIGHT);
+        pet.x = WIDTH - pet.pet_width;
+        pet.y = HEIGHT - pet.pet_height;
+
+        advance(&mut pet, 400.0, 300.0, &mut now, 0.016);
+
+        assert_in_bounds(&pet, 400.0, 300.0);
+    }
+
+    #[test]
+    fn air_drag_slows_a_thrown_pet() {
+        let (mut pet, mut now) = physics_pet(4000.0, 4000.0);
+        pet.x = 0.0;
+        pet.y = 0.0;
+        pet.is_on_ground = false;
+        pet.velocity_x = 300.0;
+
+        let mut last_speed = pet.velocity_x;
+        for _ in 0..30 {
+            advance(&mut pet, 4000.0, 4000.0, &mut now, 1.0 / 60.0);
+            assert!(!pet.is_on_ground);
+            assert!(pet.velocity_x < last_speed, "{} did not drop below {}", pet.velocity_x, last_speed);
+            last_speed = pet.velocity_x;
+        }
+    }
+
+    #[test]
+    fn ground_friction_brings_a_sliding_pet_to_rest() {
+        let (mut pet, mut now) = physics_pet(WIDTH, HEIGHT);
+        pet.behavior_mode = BehaviorMode::Wander;
+        pet.idle_duration = f32::MAX; // no walking off on its own
+        pet.velocity_x = 150.0;
+
+        for _ in 0..300 {
+            advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        }
+
+        assert!(pet.velocity_x.abs() < IDLE_SPEED_THRESHOLD);
+        assert!(!matches!(pet.animation_state, AnimationState::RunningRight | AnimationState::RunningLeft));
+    }
+
+    #[test]
+    fn same_seed_makes_the_same_choices() {
+        let mut first = PetState::with_seed(42, WIDTH, HEIGHT);
+        let mut second = PetState::with_seed(42, WIDTH, HEIGHT);
+
+        for _ in 0..20 {
+            first.fall_asleep();
+            second.fall_asleep();
+            assert_eq!(first.action_timer, second.action_timer);
+
+            first.choose_idle_animation();
+            second.choose_idle_animation();
+            assert_eq!(first.animation_state, second.animation_state);
+        }
+    }
+
+    #[test]
+    fn same_seed_and_clock_replay_the_same_path() {
+        let (mut first, mut first_now) = physics_pet(WIDTH, HEIGHT);
+        let (mut second, mut second_now) = physics_pet(WIDTH, HEIGHT);
+        // Left to wander, so the path depends on the random choices
+        first.behavior_mode = BehaviorMode::Wander;
+        second.behavior_mode = BehaviorMode::Wander;
+
+        for _ in 0..600 {
+            advance(&mut first, WIDTH, HEIGHT, &mut first_now, 1.0 / 60.0);
+            advance(&mut second, WIDTH, HEIGHT, &mut second_now, 1.0 / 60.0);
+            assert_eq!((first.x, first.y), (second.x, second.y));
+            assert_eq!(first.animation_state, second.animation_state);
+        }
+
+        // No time passing means no movement
+        let (x, y) = (first.x, first.y);
+        first.update(WIDTH, HEIGHT, None, first_now);
+        assert_eq!((first.x, first.y), (x, y));
+    }
+
+    #[test]
+    fn tiny_window_spawns_pet_inside_it() {
+        let pet = PetState::with_seed(7, 80.0, 80.0);
+        assert_in_bounds(&pet, 80.0, 80.0);
+
+        let pet = PetState::with_seed(7, 40.0, 30.0);
+        assert!(pet.pet_width <= 40.0 && pet.pet_height <= 30.0);
+        assert_in_bounds(&pet, 40.0, 30.0);
+    }
+
+    #[test]
+    fn falling_pet_never_passes_terminal_velocity() {
+        let (mut pet, mut now) = physics_pet(WIDTH, 10_000.0);
+        pet.teleport(0.0, 0.0);
+        pet.is_on_ground = false;
+        pet.jumps_remaining = 0; // no double jumps on the way down
+
+        for _ in 0..180 {
+            advance(&mut pet, WIDTH, 10_000.0, &mut now, 1.0 / 60.0);
+            assert!(pet.velocity_y <= pet.physics.terminal_velocity);
+        }
+        assert!(!pet.is_on_ground);
+        assert_eq!(pet.velocity_y, pet.physics.terminal_velocity);
+    }
+
+    #[test]
+    fn edge_offsets_keep_the_real_window_size() {
+        let (mut pet, mut now) = physics_pet(WIDTH, HEIGHT);
+        pet.physics.ground_offset = 50.0;
+        pet.physics.top_offset = 100.0;
+
+        for _ in 0..60 {
+            advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+            assert_eq!((pet.window_width, pet.window_height), (WIDTH, HEIGHT));
+        }
+        assert!(pet.is_on_ground);
+        assert_eq!(pet.y, HEIGHT - 50.0 - pet.pet_height);
+    }
+
+    #[test]
+    fn run_animation_holds_between_the_thresholds() {
+        let (mut pet, mut now) = physics_pet(WIDTH, HEIGHT);
+        pet.physics.ground_friction = 0.0; // keeps whatever speed it is given
+
+        // In the band between 3 and 8 px/s the animation stays as it was
+        pet.velocity_x = 5.0;
+        pet.animation_state = AnimationState::IdleRight;
+        for _ in 0..10 {
+            advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+            assert_ne!(pet.animation_state, AnimationState::RunningRight);
+        }
+
+        pet.velocity_x = 9.0;
+        advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        assert_eq!(pet.animation_state, AnimationState::RunningRight);
+
+        pet.velocity_x = 5.0;
+        for _ in 0..10 {
+            advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+            assert_eq!(pet.animation_state, AnimationState::RunningRight);
+        }
+
+        pet.velocity_x = 2.0;
+        advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        assert_ne!(pet.animation_state, AnimationState::RunningRight);
+    }
+
+    #[test]
+    fn first_update_is_a_zero_length_step() {
+        let mut pet = PetState::with_seed(7, WIDTH, HEIGHT);
+        pet.behavior_mode = BehaviorMode::ManualControl;
+        pet.teleport(100.0, 0.0);
+        pet.is_on_ground = false;
+        pet.velocity_x = 100.0;
+        assert!(pet.last_update.is_none());
+
+        let now = Instant::now();
+        pet.update(WIDTH, HEIGHT, None, now);
+
+        assert_eq!((pet.x, pet.y), (100.0, 0.0));
+        assert_eq!((pet.velocity_x, pet.velocity_y), (100.0, 0.0));
+        assert_eq!(pet.sim_time, 0.0);
+        assert_eq!(pet.last_update, Some(now));
+
+        pet.update(WIDTH, HEIGHT, None, now + Duration::from_millis(50));
+        assert!(pet.x > 100.0 && pet.y > 0.0);
+    }
+
+    #[test]
+    fn animation_names_match_their_serde_names() {
+        for state in AnimationState::ALL {
+            assert_eq!(serde_json::to_string(&state).unwrap(), format!("\"{}\"", state.name()));
+            assert_eq!(state.name().parse::<AnimationState>(), Ok(state));
+        }
+        assert!("sideways-right".parse::<AnimationState>().is_err());
+    }
+
+    #[test]
+    fn non_finite_state_is_recovered() {
+        let (mut pet, mut now) = physics_pet(WIDTH, HEIGHT);
+        pet.velocity_x = f32::NAN;
+        pet.velocity_y = f32::INFINITY;
+
+        advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        assert!(pet.velocity_x.is_finite() && pet.velocity_y.is_finite());
+        assert!(pet.x.is_finite() && pet.y.is_finite());
+        assert_in_bounds(&pet, WIDTH, HEIGHT);
+
+        pet.x = f32::NAN;
+        advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        assert!(pet.x.is_finite());
+        assert_in_bounds(&pet, WIDTH, HEIGHT);
+    }
+
+    #[test]
+    fn slow_floor_impact_settles_instead_of_bouncing() {
+        let (mut pet, mut now) = physics_pet(WIDTH, HEIGHT);
+        pet.physics.restitution_floor = 0.5;
+        let floor = HEIGHT - pet.pet_height;
+
+        // Half of 60 px/s is under the 40 px/s threshold
+        pet.teleport(100.0, floor - 1.0);
+        pet.is_on_ground = false;
+        pet.velocity_y = 60.0;
+        for _ in 0..60 {
+            advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+            if pet.is_on_ground {
+                break;
+            }
+        }
+        assert!(pet.is_on_ground);
+        for _ in 0..60 {
+            advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+            assert!(pet.is_on_ground);
+            assert_eq!((pet.y, pet.velocity_y), (floor, 0.0));
+        }
+
+        // A hard landing still bounces
+        pet.teleport(100.0, floor - 1.0);
+        pet.is_on_ground = false;
+        pet.velocity_y = 400.0;
+        advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        assert!(pet.velocity_y < 0.0);
+    }
+
+    #[test]
+    fn saved_pets_follow_the_configured_count() {
+        let saved = fresh_pets(WIDTH, HEIGHT, 3);
+
+        let pets = match_pet_count(saved.clone(), 1, WIDTH, HEIGHT);
+        assert_eq!(pets.iter().map(|pet| pet.id).collect::<Vec<_>>(), [0]);
+
+        let pets = match_pet_count(saved, 5, WIDTH, HEIGHT);
+        assert_eq!(pets.iter().map(|pet| pet.id).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+        for pet in &pets {
+            assert_in_bounds(pet, WIDTH, HEIGHT);
+        }
+    }
+
+    // Count the jumps a wandering pet makes over `seconds` of ticks at `hz`
+    fn jumps_at(seed: u64, hz: f32, seconds: f32) -> usize {
+        let mut pet = PetState::with_seed(seed, WIDTH, HEIGHT);
+        pet.behavior_mode = BehaviorMode::Wander;
+        pet.behavior.set_jump_probability(1.0);
+
+        let mut now = Instant::now();
+        pet.update(WIDTH, HEIGHT, None, now);
+        let mut jumps = 0;
+        for _ in 0..(seconds * hz) as usize {
+            advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / hz);
+            jumps += pet.events.drain(..).filter(|event| matches!(event, PhysicsEvent::Jumped(_))).count();
+        }
+        jumps
+    }
+
+    #[test]
+    fn jump_rate_does_not_depend_on_the_tick_rate() {
+        let slow: usize = (1..=16).map(|seed| jumps_at(seed, 30.0, 600.0)).sum();
+        let fast: usize = (1..=16).map(|seed| jumps_at(seed, 144.0, 600.0)).sum();
+
+        assert!(slow > 100, "too few jumps to compare: {}", slow);
+        let ratio = fast as f32 / slow as f32;
+        assert!((0.85..=1.15).contains(&ratio), "{} jumps at 30 Hz, {} at 144 Hz", slow, fast);
+    }
+
+    #[test]
+    fn reduced_motion_impulse_stays_on_the_ground() {
+        let (mut pet, _) = physics_pet(WIDTH, HEIGHT);
+        pet.behavior.reduced_motion = true;
+        pet.teleport(WIDTH / 2.0, HEIGHT - pet.pet_height);
+        pet.is_on_ground = true;
+
+        pet.apply_impulse(MAX_THROW_SPEED, -MAX_THROW_SPEED);
+
+        assert!(pet.is_on_ground);
+        assert!(pet.velocity_x.abs() <= REDUCED_MOTION_SPEED);
+        assert!(pet.velocity_y >= 0.0);
+    }
+
+    #[test]
+    fn reduced_motion_holds_against_wind_and_attractor() {
+        let (mut pet, mut now) = physics_pet(WIDTH, HEIGHT);
+        pet.behavior.reduced_motion = true;
+        pet.physics.wind = 5000.0;
+        pet.teleport(100.0, HEIGHT - pet.pet_height);
+        pet.is_on_ground = true;
+        pet.attractor = Some(Attractor { x: WIDTH / 2.0, y: 0.0, strength: MAX_ATTRACTOR_STRENGTH });
+
+        for _ in 0..60 {
+            advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+            assert!(pet.is_on_ground);
+            assert!(pet.velocity_x.abs() <= REDUCED_MOTION_SPEED);
+        }
+    }
+
+    #[test]
+    fn defaults_pass_the_setter_checks() {
+        assert_eq!(PhysicsConfig::new().validate(), Ok(()));
+        assert_eq!(BehaviorConfig::new().validate(), Ok(()));
+
+        let mut physics = PhysicsConfig::new();
+        physics.restitution_floor = 2.0;
+        assert!(physics.validate().is_err());
+
+        let mut behavior = BehaviorConfig::new();
+        behavior.burst_intensity = f32::NAN;
+        assert!(behavior.validate().is_err());
+    }
+
+    #[test]
+    fn added_pets_start_like_the_primary() {
+        let mut saved = fresh_pets(WIDTH, HEIGHT, 1);
+        saved[0].pet_width = 120.0;
+        saved[0].pet_height = 90.0;
+        saved[0].confinement = Some(Confinement { x: 0.0, y: 0.0, width: WIDTH, height: HEIGHT });
+        saved[0].home = Some((100.0, HEIGHT));
+        saved[0].attractor = Some(Attractor { x: 0.0, y: 0.0, strength: 1000.0 });
+
+        let pets = match_pet_count(saved, 3, WIDTH, HEIGHT);
+        for pet in &pets[1..] {
+            assert_eq!((pet.pet_width, pet.pet_height), (120.0, 90.0));
+            assert!(pet.confinement.is_some());
+            assert_eq!(pet.home, Some((100.0, HEIGHT)));
+            assert!(pet.attractor.is_some());
+            assert_in_bounds(pet, WIDTH, HEIGHT);
+            assert_eq!(pet.y, HEIGHT - 90.0);
+        }
+    }
+
+    #[test]
+    fn leaving_the_ground_upwards_is_a_jump_whatever_lifted_it() {
+        let jumped = |pet: &PetState| pet.events.iter().any(|event| matches!(event, PhysicsEvent::Jumped(_)));
+        let (mut pet, mut now) = physics_pet(WIDTH, HEIGHT);
+        advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        assert!(pet.is_on_ground);
+
+        // A hop given between updates, like pet_clicked's
+        pet.velocity_y = pet.physics.jump_force * 0.5;
+        pet.is_on_ground = false;
+        advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        assert!(jumped(&pet));
+
+        // Lifted by the attractor during the update
+        for _ in 0..120 {
+            advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        }
+        assert!(pet.is_on_ground);
+        pet.attractor = Some(Attractor { x: pet.x, y: 0.0, strength: MAX_ATTRACTOR_STRENGTH });
+        advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        assert!(jumped(&pet));
+
+        // Teleporting off the floor just drops the pet
+        pet.attractor = None;
+        for _ in 0..300 {
+            advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        }
+        assert!(pet.is_on_ground);
+        pet.teleport(100.0, 100.0);
+        advance(&mut pet, WIDTH, HEIGHT, &mut now, 1.0 / 60.0);
+        assert!(!jumped(&pet));
+    }
+}
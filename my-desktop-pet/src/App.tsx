@@ -1,4 +1,5 @@
 import { useState, useEffect, useRef, useCallback } from "react";
+import type { MouseEvent as ReactMouseEvent } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { Window } from "@tauri-apps/api/window";
 import { listen } from "@tauri-apps/api/event";
@@ -9,7 +10,12 @@ import redPandaSpriteSheet from "./assets/Red Panda Sprite Sheet.png";
 import Settings from "./Settings";
 
 //Types
-import type { PetStats } from "./types";
+import type {
+  AnimationMetadata,
+  GravityDirection,
+  PetStats,
+  PetSnapshot,
+} from "./types";
 
 // Constants for configuration
 const DEFAULT_WINDOW_WIDTH = 1920;
@@ -29,9 +35,27 @@ const getBaseAnimation = (state: string) => {
   return state.replace(/-(left|right)$/, "");
 };
 
+// How far to turn the sprite so its feet point at the edge gravity pulls towards
+const GRAVITY_ROTATION: Record<GravityDirection, number> = {
+  down: 0,
+  up: 180,
+  left: 90,
+  right: -90,
+};
+
+// Animations that reuse another one's frames (drawn differently) when a
+// sprite sheet has no dedicated row for them
+const ANIMATION_ALIASES: Record<string, string> = {
+  ceiling: "run",
+  happy: "idle-alt-1",
+  "idle-breathing": "idle",
+  startled: "jump",
+};
+
 const getAnimationConfig = (
   animations: unknown,
   animationState: string,
+  metadata: Record<string, AnimationMetadata> = {},
 ): AnimationConfig => {
   const animationMap = animations as Record<string, AnimationConfig>;
   const baseAnimation = getBaseAnimation(animationState);
@@ -39,7 +63,18 @@ const getAnimationConfig = (
   // Not every pet sprite sheet has every animation.
   // If Rust asks for an idle variant the current pet does not have,
   // safely fall back to that pet's normal idle animation.
-  return animationMap[baseAnimation] ?? animationMap.idle;
+  const config =
+    animationMap[baseAnimation] ??
+    animationMap[ANIMATION_ALIASES[baseAnimation]] ??
+    animationMap.idle;
+
+  // Timing set in pet_config.toml wins over the built-in one
+  const override = metadata[animationState];
+  if (!override) return config;
+  return {
+    frames: config.frames.slice(0, override.frame_count),
+    frameDuration: 1000 / override.fps,
+  };
 };
 
 // Animation Sequence coordinates
@@ -103,6 +138,11 @@ const CAT_ANIMATIONS = {
     ],
     frameDuration: 150,
   },
+  // Short squash after touching down, held on the last falling frame
+  landing: {
+    frames: [[320, 320]],
+    frameDuration: 150,
+  },
   sleep: {
     frames: [
       [0, 384],
@@ -172,6 +212,10 @@ const FOX_ANIMATIONS = {
     ],
     frameDuration: 150,
   },
+  landing: {
+    frames: [[640, 192]],
+    frameDuration: 150,
+  },
   sleep: {
     frames: [
       [0, 320],
@@ -250,6 +294,10 @@ const RED_PANDA_ANIMATIONS = {
     ],
     frameDuration: 100,
   },
+  landing: {
+    frames: [[256, 128]],
+    frameDuration: 150,
+  },
   sleep: {
     frames: [
       [0, 384],
@@ -277,8 +325,158 @@ const PET_CONFIG = {
 
 export type PetType = keyof typeof PET_CONFIG;
 
+type SpriteSheet = (typeof PET_CONFIG)[PetType]["spriteSheet"];
+
+// Style for one frame of a sprite sheet, flipped and squashed to match the
+// pet's animation state
+const getFrameStyle = (
+  [x, y]: readonly [number, number],
+  animationState: string,
+  spriteSheet: SpriteSheet,
+  squash: { x: number; y: number },
+  opacity: number,
+) => {
+  const isFlipped = animationState.endsWith("-left");
+  // Walking along the top of the window, so draw the pet upside down
+  const isUpsideDown = animationState.startsWith("ceiling-");
+  // Breathing reuses the idle frames, sunk by a pixel so the pet bobs
+  const bob = animationState.startsWith("idle-breathing-") ? 1 : 0;
+
+  return {
+    width: `${FRAME_WIDTH}px`,
+    height: `${FRAME_HEIGHT}px`,
+    backgroundImage: `url(${spriteSheet})`,
+    backgroundPosition: `-${x}px -${y}px`,
+    backgroundSize: `${spriteSheet.width}px ${spriteSheet.height}px`,
+    backgroundRepeat: "no-repeat",
+    transform: `translateY(${bob}px) scaleX(${(isFlipped ? -1 : 1) * squash.x}) scaleY(${(isUpsideDown ? -1 : 1) * squash.y})`,
+    transformOrigin: "center",
+    opacity,
+    imageRendering: "pixelated" as const,
+    willChange: "transform, background-position",
+  };
+};
+
+type PetBodyProps = {
+  size: { width: number; height: number };
+  gravity: GravityDirection;
+  frameStyle: ReturnType<typeof getFrameStyle>;
+};
+
+// The pet's box with the sprite drawn inside it
+function PetBody({ size, gravity, frameStyle }: PetBodyProps) {
+  const isSideways = gravity === "left" || gravity === "right";
+  const bodySize = isSideways
+    ? { width: size.height, height: size.width }
+    : size;
+
+  return (
+    <div
+      style={{
+        width: `${size.width}px`,
+        height: `${size.height}px`,
+        position: "relative",
+      }}
+    >
+      {/* Stand the pet on whichever edge it falls towards. On the side
+          walls its box is turned, so width and height swap places. */}
+      <div
+        style={{
+          position: "absolute",
+          left: "50%",
+          top: "50%",
+          width: `${bodySize.width}px`,
+          height: `${bodySize.height}px`,
+          transform: `translate(-50%, -50%) rotate(${GRAVITY_ROTATION[gravity]}deg)`,
+        }}
+      >
+        {/* Scale the 64px sprite frame up or down to the pet's size */}
+        <div
+          style={{
+            transform: `scale(${bodySize.width / FRAME_WIDTH}, ${bodySize.height / FRAME_HEIGHT})`,
+            transformOrigin: "top left",
+          }}
+        >
+          <div style={frameStyle} draggable={false} />
+        </div>
+      </div>
+    </div>
+  );
+}
+
+type CompanionPetProps = {
+  snapshot: PetSnapshot;
+  defaultPet: PetType;
+  metadata: Record<string, AnimationMetadata>;
+};
+
+// One of the extra pets from pet_count or add_pet. They are only drawn, the
+// mouse and the settings panel act on the primary pet.
+function CompanionPet({ snapshot, defaultPet, metadata }: CompanionPetProps) {
+  const [frameIndex, setFrameIndex] = useState(0);
+  const config = PET_CONFIG[snapshot.skin as PetType] ?? PET_CONFIG[defaultPet];
+  const animationSpeedRef = useRef(snapshot.animation_speed);
+  animationSpeedRef.current = snapshot.animation_speed;
+
+  // Each new animation starts from its first frame
+  useEffect(() => {
+    setFrameIndex(0);
+  }, [snapshot.animation]);
+
+  useEffect(() => {
+    const animation = getAnimationConfig(
+      config.animations,
+      snapshot.animation,
+      metadata,
+    );
+    const timer = setTimeout(() => {
+      setFrameIndex((prev) => (prev + 1) % animation.frames.length);
+    }, animation.frameDuration / animationSpeedRef.current);
+
+    return () => clearTimeout(timer);
+  }, [frameIndex, snapshot.animation, config, metadata]);
+
+  const { frames } = getAnimationConfig(
+    config.animations,
+    snapshot.animation,
+    metadata,
+  );
+  const frame = frames[Math.min(frameIndex, frames.length - 1)];
+
+  return (
+    <div
+      className="absolute pointer-events-none"
+      style={{
+        left: `${snapshot.x}px`,
+        top: `${snapshot.y}px`,
+        transition: "top 50ms linear, left 50ms linear",
+      }}
+    >
+      <PetBody
+        size={{ width: snapshot.width, height: snapshot.height }}
+        gravity={snapshot.gravity}
+        frameStyle={getFrameStyle(
+          frame,
+          snapshot.animation,
+          config.spriteSheet,
+          { x: snapshot.scale_x, y: snapshot.scale_y },
+          snapshot.opacity,
+        )}
+      />
+    </div>
+  );
+}
+
 function App() {
   const [position, setPosition] = useState({ x: 0, y: 0 });
+  const [petSize, setPetSize] = useState({
+    width: FRAME_WIDTH,
+    height: FRAME_HEIGHT,
+  });
+  const [gravity, setGravity] = useState<GravityDirection>("down");
+  const [squash, setSquash] = useState({ x: 1, y: 1 });
+  const [opacity, setOpacity] = useState(1);
+  const [companions, setCompanions] = useState<PetSnapshot[]>([]);
   const [windowSize, setWindowSize] = useState({
     width: DEFAULT_WINDOW_WIDTH,
     height: DEFAULT_WINDOW_HEIGHT,
@@ -287,9 +485,13 @@ function App() {
   const [isReady, setIsReady] = useState(false);
   const [animationState, setAnimationState] = useState("idle-right");
   const [frameIndex, setFrameIndex] = useState(0);
+  const [animationMetadata, setAnimationMetadata] = useState<
+    Record<string, AnimationMetadata>
+  >({});
   const [currentPet, setCurrentPet] = useState<PetType>("cat");
   const [settingsOpen, setSettingsOpen] = useState(false);
   const [isVisible, setIsVisible] = useState(true);
+  const [isWindowDragMode, setIsWindowDragMode] = useState(false);
   const [hearts, setHearts] = useState<
     { id: number; xOffset: number; size: number }[]
   >([]);
@@ -303,9 +505,12 @@ function App() {
   //Ref
   const windowRef = useRef<HTMLDivElement>(null);
   const animationTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
-  const positionUpdateIntervalRef = useRef<ReturnType<
+  const statsUpdateIntervalRef = useRef<ReturnType<
     typeof setInterval
   > | null>(null);
+  const animationStateRef = useRef(animationState);
+  const isGrabbingRef = useRef(false);
+  const animationSpeedRef = useRef(1);
 
   const currentConfig = PET_CONFIG[currentPet];
   const currentSpriteSheet = currentConfig.spriteSheet;
@@ -322,6 +527,46 @@ function App() {
     return () => document.removeEventListener("keydown", handleKeyDown);
   }, []);
 
+  // Arrow keys drive the pet in manual-control mode
+  useEffect(() => {
+    const commands: Record<string, string> = {
+      ArrowLeft: "move_left",
+      ArrowRight: "move_right",
+      ArrowUp: "jump",
+    };
+    const handleKeyDown = (e: KeyboardEvent) => {
+      const command = commands[e.key];
+      if (!command || settingsOpen) return;
+      e.preventDefault();
+      invoke(command).catch((error) =>
+        console.error(`Failed to invoke ${command}:`, error)
+      );
+    };
+    document.addEventListener("keydown", handleKeyDown);
+    return () => document.removeEventListener("keydown", handleKeyDown);
+  }, [settingsOpen]);
+
+  useEffect(() => {
+    animationStateRef.current = animationState;
+  }, [animationState]);
+
+  // Throw the pet when the mouse is released after grabbing it
+  useEffect(() => {
+    const handleMouseUp = async () => {
+      if (!isGrabbingRef.current) return;
+      isGrabbingRef.current = false;
+      try {
+        await invoke("release_pet");
+      } catch (error) {
+        console.error("Failed to release pet:", error);
+      }
+    };
+    document.addEventListener("mouseup", handleMouseUp);
+    return () => {
+      document.removeEventListener("mouseup", handleMouseUp);
+    };
+  }, []);
+
   // Get window size with memoized callback to prevent unnecessary renders
   const getWindowSize = useCallback(async () => {
     try {
@@ -399,7 +644,11 @@ function App() {
   useEffect(() => {
     if (!isLoaded) return;
 
-    const config = getAnimationConfig(currentAnimations, animationState);
+    const config = getAnimationConfig(
+      currentAnimations,
+      animationState,
+      animationMetadata,
+    );
 
     // Clear any existing timer
     if (animationTimerRef.current) {
@@ -409,7 +658,7 @@ function App() {
     // Set new timer for frame animation
     animationTimerRef.current = setTimeout(() => {
       setFrameIndex((prev) => (prev + 1) % config.frames.length);
-    }, config.frameDuration);
+    }, config.frameDuration / animationSpeedRef.current);
 
     // Clean up on unmount
     return () => {
@@ -417,63 +666,100 @@ function App() {
         clearTimeout(animationTimerRef.current);
       }
     };
-  }, [frameIndex, animationState, isLoaded, currentAnimations]);
-
-  // Update pet position at regular intervals
+  }, [
+    frameIndex,
+    animationState,
+    isLoaded,
+    currentAnimations,
+    animationMetadata,
+  ]);
+
+  // Follow the position and animation pushed by the Rust physics tick
   useEffect(() => {
-    if (!isLoaded || windowSize.width <= 0 || windowSize.height <= 0) {
-      return;
-    }
+    if (!isLoaded) return;
 
-    // Function to update position via Rust backend
-    const updatePosition = async () => {
+    let unlisten: (() => void) | undefined;
+
+    const setupPetListener = async () => {
       try {
-        const [x, y, state] = await invoke<[number, number, string]>(
-          "get_pet_movement",
-          {
-            windowWidth: windowSize.width,
-            windowHeight: windowSize.height,
-          },
-        );
-
-        setPosition({ x, y });
-
-        // Only change the animation state if it's different
-        if (state !== animationState) {
-          setAnimationState(state);
-          setFrameIndex(0); // Reset frame index when changing animation
-        }
+        // Every pet comes in each update, the first is the primary one
+        unlisten = await listen<PetSnapshot[]>("pet-update", (event) => {
+          const [primary, ...others] = event.payload;
+          if (!primary) return;
+          const { x, y, width, height, animation } = primary;
+
+          setCompanions(others);
+          setPosition({ x, y });
+          setPetSize({ width, height });
+          setGravity(primary.gravity);
+          animationSpeedRef.current = primary.animation_speed;
+          setSquash({ x: primary.scale_x, y: primary.scale_y });
+          setOpacity(primary.opacity);
+
+          // Only change the animation state if it's different
+          if (animation !== animationStateRef.current) {
+            animationStateRef.current = animation;
+            setAnimationState(animation);
+            setFrameIndex(0); // Reset frame index when changing animation
+          }
+        });
       } catch (error) {
-        console.error("Failed to update pet position:", error);
+        console.error("Failed to listen for pet updates:", error);
       }
+    };
 
-      const [affection, hunger, energy, mood] =
-        await invoke<[number, number, number, string]>("get_pet_stats");
+    setupPetListener();
 
-      setPetStats({
-        affection,
-        hunger,
-        energy,
-        mood,
-      });
+    return () => {
+      if (unlisten) unlisten();
     };
+  }, [isLoaded]);
 
-    // Initial position update
-    updatePosition();
+  // Frame counts and speeds configured on the Rust side
+  useEffect(() => {
+    if (!isLoaded) return;
+
+    invoke<Record<string, AnimationMetadata>>("get_animation_metadata")
+      .then(setAnimationMetadata)
+      .catch((error) =>
+        console.error("Failed to load animation metadata:", error),
+      );
+  }, [isLoaded]);
 
-    // Set interval for regular updates
-    positionUpdateIntervalRef.current = setInterval(
-      updatePosition,
+  // Update pet stats at regular intervals
+  useEffect(() => {
+    if (!isLoaded) return;
+
+    const updateStats = async () => {
+      try {
+        const [affection, hunger, energy, mood] =
+          await invoke<[number, number, number, string]>("get_pet_stats");
+
+        setPetStats({
+          affection,
+          hunger,
+          energy,
+          mood,
+        });
+      } catch (error) {
+        console.error("Failed to update pet stats:", error);
+      }
+    };
+
+    updateStats();
+
+    statsUpdateIntervalRef.current = setInterval(
+      updateStats,
       UPDATE_INTERVAL_MS,
     );
 
     // Clean up on unmount
     return () => {
-      if (positionUpdateIntervalRef.current) {
-        clearInterval(positionUpdateIntervalRef.current);
+      if (statsUpdateIntervalRef.current) {
+        clearInterval(statsUpdateIntervalRef.current);
       }
     };
-  }, [windowSize, isLoaded, animationState]);
+  }, [isLoaded]);
 
   // Toggle click-through based on settings state
   useEffect(() => {
@@ -512,46 +798,59 @@ function App() {
     };
   }, []);
 
+  // Holding the move-window shortcut (Ctrl+Alt+M) lets the whole window be dragged
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+
+    const setupDragModeListener = async () => {
+      try {
+        unlisten = await listen<boolean>("window-drag-mode", (event) => {
+          setIsWindowDragMode(event.payload);
+        });
+      } catch (error) {
+        console.error("Failed to listen for window drag mode:", error);
+      }
+    };
+    setupDragModeListener();
+
+    return () => {
+      if (unlisten) unlisten();
+    };
+  }, []);
+
   // Get the current frame from the animation sequence
   const getCurrentFrame = useCallback(() => {
-    const animation = getAnimationConfig(currentAnimations, animationState);
+    const animation = getAnimationConfig(
+      currentAnimations,
+      animationState,
+      animationMetadata,
+    );
 
     const safeIndex = Math.min(frameIndex, animation.frames.length - 1);
     return animation.frames[safeIndex];
-  }, [animationState, frameIndex, currentAnimations]);
+  }, [animationState, frameIndex, currentAnimations, animationMetadata]);
 
   // Calculate sprite style based on current frame
   const getSpriteStyle = useCallback(() => {
-    const [x, y] = getCurrentFrame();
-    const isFlipped = animationState.endsWith("-left");
-
-    return {
-      width: `${FRAME_WIDTH}px`,
-      height: `${FRAME_HEIGHT}px`,
-      backgroundImage: `url(${currentSpriteSheet})`,
-      backgroundPosition: `-${x}px -${y}px`,
-      backgroundSize: `${currentSpriteSheet.width}px ${currentSpriteSheet.height}px`,
-      backgroundRepeat: "no-repeat",
-      transform: isFlipped ? "scaleX(-1)" : "scaleX(1)",
-      transformOrigin: "center",
-      imageRendering: "pixelated" as const,
-      willChange: "transform, background-position",
-    };
-  }, [getCurrentFrame, animationState, currentSpriteSheet]);
+    return getFrameStyle(
+      getCurrentFrame(),
+      animationState,
+      currentSpriteSheet,
+      squash,
+      opacity,
+    );
+  }, [getCurrentFrame, animationState, currentSpriteSheet, squash, opacity]);
 
   // Reset pet position handler
   const handleReset = async () => {
     try {
-      const [x, y, state] = await invoke<[number, number, string]>(
-        "reset_pet_position",
-        {
-          windowWidth: windowSize.width,
-          windowHeight: windowSize.height,
-        },
-      );
+      const snapshot = await invoke<PetSnapshot>("reset_pet_position", {
+        windowWidth: windowSize.width,
+        windowHeight: windowSize.height,
+      });
 
-      setPosition({ x, y });
-      setAnimationState(state);
+      setPosition({ x: snapshot.x, y: snapshot.y });
+      setAnimationState(snapshot.animation);
       setFrameIndex(0);
     } catch (error) {
       console.error("Failed to reset pet position:", error);
@@ -563,8 +862,17 @@ function App() {
     setFrameIndex(0);
   }, []);
 
+  const handlePetMouseDown = async (e: ReactMouseEvent) => {
+    isGrabbingRef.current = true;
+    try {
+      await invoke("grab_pet", { cursorX: e.clientX, cursorY: e.clientY });
+    } catch (error) {
+      console.error("Failed to grab pet:", error);
+    }
+  };
+
   const handlePetClick = async () => {
-    await invoke("pet_pet");
+    await invoke("pet_clicked");
     const id = Date.now();
 
     setHearts((prev) => [
@@ -593,6 +901,13 @@ function App() {
         backgroundColor: "transparent", // Make the background transparent
       }}
     >
+      {isWindowDragMode && (
+        <div
+          data-tauri-drag-region
+          className="fixed inset-0 cursor-move"
+          style={{ backgroundColor: "rgba(59, 130, 246, 0.1)", zIndex: 40 }}
+        />
+      )}
       {isReady && isVisible && (
         <div
           className="absolute"
@@ -601,6 +916,7 @@ function App() {
             top: `${position.y}px`,
             transition: "top 50ms linear, left 50ms linear", // Smooth movement
           }}
+          onMouseDown={handlePetMouseDown}
           onClick={handlePetClick}
         >
           {hearts.map((heart) => (
@@ -620,9 +936,23 @@ function App() {
               ❤️
             </div>
           ))}
-          <div style={getSpriteStyle()} draggable={false} />
+          <PetBody
+            size={petSize}
+            gravity={gravity}
+            frameStyle={getSpriteStyle()}
+          />
         </div>
       )}
+      {isReady &&
+        isVisible &&
+        companions.map((companion) => (
+          <CompanionPet
+            key={companion.id}
+            snapshot={companion}
+            defaultPet={currentPet}
+            metadata={animationMetadata}
+          />
+        ))}
       <Settings
         isOpen={settingsOpen}
         onClose={() => setSettingsOpen(false)}
@@ -4,3 +4,97 @@ export type PetStats = {
   energy: number;
   mood: string;
 };
+
+export type GravityDirection = "down" | "up" | "left" | "right";
+
+export type BehaviorMode =
+  | "wander"
+  | "follow-cursor"
+  | "flee-cursor"
+  | "manual-control"
+  | "wander-target"
+  | "perimeter-walk";
+
+// Returned by get_behavior
+export type BehaviorSnapshot = {
+  mode: BehaviorMode;
+  jump_probability: number;
+  sleep_after_idle: number;
+  walk_weight: number;
+  run_weight: number;
+  jump_weight: number;
+  flee_radius: number;
+  idle_breathing: boolean;
+  face_away_from_walls: boolean;
+  reduced_motion: boolean;
+  fade_after_idle: number; // seconds
+  fade_rate: number; // opacity lost per second, 0 never fades
+  min_opacity: number;
+  target_acceleration: number;
+  target_arrive_radius: number;
+  target_pause: number;
+  min_ground_dwell: number;
+  bursts_per_minute: number; // 0 when bursts of energy are off
+  burst_duration: number;
+  burst_intensity: number;
+};
+
+export type PetSnapshot = {
+  id: number;
+  x: number;
+  y: number;
+  velocity_x: number;
+  velocity_y: number;
+  is_on_ground: boolean;
+  facing_direction: boolean;
+  width: number;
+  height: number;
+  animation: string;
+  stamina: number; // 0 to 100
+  gravity: GravityDirection;
+  skin: string; // sprite sheet name, empty for the currently selected pet
+  animation_speed: number; // frame rate multiplier, 1 is the authored speed
+  scale_x: number; // squash-and-stretch hints, 1 is the normal shape
+  scale_y: number;
+  frame_count: number;
+  sim_time: number; // seconds of simulation since startup
+  opacity: number; // 0 to 1, lower while the pet sits idle
+};
+
+// Per-animation timing from get_animation_metadata, keyed by animation state
+export type AnimationMetadata = {
+  frame_count: number;
+  fps: number;
+};
+
+// Payload of the "pet-jumped" and "pet-landed" events
+export type PhysicsEventPayload = {
+  id: number;
+  speed: number; // take-off or impact speed in px/s
+};
+
+// Returned by list_monitors, in physical pixels. The work area leaves out the
+// taskbar / dock / menu bar.
+export type MonitorInfo = {
+  index: number;
+  name: string | null;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  work_x: number;
+  work_y: number;
+  work_width: number;
+  work_height: number;
+  scale_factor: number;
+  is_primary: boolean;
+};
+
+// Returned by get_perf_stats, over the last couple of seconds of physics ticks
+export type PerfStats = {
+  average_delta_ms: number;
+  max_delta_ms: number;
+  updates_per_second: number;
+  target_hz: number;
+  samples: number;
+};